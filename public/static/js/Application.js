@@ -54,11 +54,14 @@ define(["knockout", "reqwest", "moment", "vega", "vega-embed", "Database", "Util
 	const SORT_USER = "SortUser";
 	const SORT_SIZE = "SortSize";
 	const SORT_MODIFIED = "SortModified";
+	const SORT_LAST_ACTIVE = "SortLastActive";
 
 	const ORDER_ASC = "OrderAsc";
 	const ORDER_DESC = "OrderDesc";
 
 	const Application = function() {
+		this.servers = ko.observableArray([]);
+		this.selectedServer = ko.observable();
 		this.diskUsed = ko.observable(0.0);
 		this.diskCapacity = ko.observable(0.0);
 		this.softThreshold = ko.observable(0.0);
@@ -69,6 +72,9 @@ define(["knockout", "reqwest", "moment", "vega", "vega-embed", "Database", "Util
 		this.isLoading = ko.observable(false);
 		this.isError = ko.observable(false);
 		this.errorMessage = ko.observable();
+		this.isStale = ko.observable(false);
+		this.updated = ko.observable(null);
+		this.workerError = ko.observable(null);
 		this.isChartAvailable = ko.observable(false);
 		this.isChartRequired = ko.observable(true);
 
@@ -146,6 +152,25 @@ define(["knockout", "reqwest", "moment", "vega", "vega-embed", "Database", "Util
 			return result;
 		}, this);
 
+		this.cssForLastActive = ko.pureComputed(function() {
+			const isColumnMatches = this.sortColumn() === SORT_LAST_ACTIVE;
+			const result = {};
+
+			result["sorted"] = isColumnMatches;
+			result["ascending"] = isColumnMatches && this.sortOrder() === ORDER_ASC;
+			result["descending"] = isColumnMatches && this.sortOrder() === ORDER_DESC;
+
+			return result;
+		}, this);
+
+		this.updatedFromNow = ko.pureComputed(function() {
+			if (this.updated() === null) {
+				return "never";
+			}
+
+			return moment.unix(this.updated()).fromNow(false);
+		}, this);
+
 		this.diskUsedHuman = ko.pureComputed(function() {
 			return Util.humanSize(this.diskUsed());
 		}, this);
@@ -172,10 +197,18 @@ define(["knockout", "reqwest", "moment", "vega", "vega-embed", "Database", "Util
 			};
 		}, this);
 
+		this.hasServers = ko.pureComputed(function() {
+			return this.servers().length > 1;
+		}, this);
+
 		this.hasResults = ko.pureComputed(function() {
 			return this.databases().length > 0;
 		}, this);
 
+		this.exportUrl = ko.pureComputed(function() {
+			return "/api/v1/export?format=csv&server=" + encodeURIComponent(this.selectedServer() || "");
+		}, this);
+
 		this.dropDatabase = function(database) {
 			const name = database.name();
 			const confirmed = window.confirm(
@@ -183,44 +216,65 @@ define(["knockout", "reqwest", "moment", "vega", "vega-embed", "Database", "Util
 			);
 
 			if (confirmed) {
-				reqwest({
-					url: "/api/v1/dropdb",
-					type: "json",
-					method: "POST",
-					contentType: "application/json",
-					data: JSON.stringify({
-						name: name,
-					}),
-				})
-					.then(
-						function(resp) {
-							if (resp.success) {
-								this.databases.remove(database);
-								this.diskUsed(this.diskUsed() - database.size());
-								this.updateChart();
-							} else {
-								this.isError(true);
-								this.errorMessage(resp.message);
-							}
-
-							this.isLoading(false);
-						}.bind(this)
-					)
-					.fail(
-						function(err, msg) {
-							this.isLoading(false);
-							this.isError(true);
-							this.errorMessage(msg || err.responseText);
-						}.bind(this)
-					);
-
-				this.isLoading(true);
-				this.isError(false);
-				this.errorMessage("");
+				this.requestDrop(database, false);
 			}
 		}.bind(this);
 
-		this.updateState();
+		this.selectedServer.subscribe(this.updateState, this);
+		this.updateServers();
+	};
+
+	Application.prototype.requestDrop = function(database, force) {
+		const name = database.name();
+
+		reqwest({
+			url: "/api/v1/dropdb",
+			type: "json",
+			method: "POST",
+			contentType: "application/json",
+			data: JSON.stringify({
+				server: this.selectedServer(),
+				name: name,
+				force: force,
+			}),
+		})
+			.then(
+				function(resp) {
+					this.isLoading(false);
+
+					if (resp.success) {
+						this.databases.remove(database);
+						this.diskUsed(this.diskUsed() - database.size());
+						this.updateChart();
+					} else if (!force && resp.details && resp.details.sessions) {
+						const forced = window.confirm(
+							"Database `" +
+								name +
+								"` has " +
+								resp.details.sessions.length +
+								" active sessions. Drop it anyway?"
+						);
+
+						if (forced) {
+							this.requestDrop(database, true);
+						}
+					} else {
+						this.isError(true);
+						this.errorMessage(resp.message);
+					}
+				}.bind(this)
+			)
+			.fail(
+				function(err, msg) {
+					this.isLoading(false);
+					this.isError(true);
+					this.errorMessage(msg || err.responseText);
+				}.bind(this)
+			);
+
+		this.isLoading(true);
+		this.isError(false);
+		this.errorMessage("");
 	};
 
 	Application.prototype.updateSortHeader = function(sortField) {
@@ -251,6 +305,8 @@ define(["knockout", "reqwest", "moment", "vega", "vega-embed", "Database", "Util
 			this.databases.sort(Util.sortBy("size", sortOrder));
 		} else if (this.sortColumn() == SORT_MODIFIED) {
 			this.databases.sort(Util.sortBy("modified", sortOrder));
+		} else if (this.sortColumn() == SORT_LAST_ACTIVE) {
+			this.databases.sort(Util.sortBy("lastActive", sortOrder));
 		}
 	};
 
@@ -274,11 +330,20 @@ define(["knockout", "reqwest", "moment", "vega", "vega-embed", "Database", "Util
 		this.sortDatabases();
 	};
 
+	Application.prototype.sortByLastActive = function() {
+		this.updateSortHeader(SORT_LAST_ACTIVE);
+		this.sortDatabases();
+	};
+
 	Application.prototype.forceUpdate = function() {
 		reqwest({
 			url: "/api/v1/update",
 			type: "json",
 			method: "POST",
+			contentType: "application/json",
+			data: JSON.stringify({
+				server: this.selectedServer(),
+			}),
 		})
 			.then(
 				function(resp) {
@@ -340,11 +405,67 @@ define(["knockout", "reqwest", "moment", "vega", "vega-embed", "Database", "Util
 		this.isChartAvailable(hasData);
 	};
 
+	Application.prototype.updateServers = function() {
+		reqwest({
+			url: "/api/v1/servers",
+			type: "json",
+			method: "POST",
+		})
+			.then(
+				function(resp) {
+					this.isLoading(false);
+
+					if (resp.success) {
+						this.servers(resp.result.map(server => server.name));
+
+						if (resp.result.length > 0) {
+							this.selectedServer(resp.result[0].name);
+						}
+					} else {
+						this.isError(true);
+						this.errorMessage(resp.message);
+					}
+				}.bind(this)
+			)
+			.fail(
+				function(err, msg) {
+					this.isLoading(false);
+					this.isError(true);
+					this.errorMessage(msg || err.responseText);
+				}.bind(this)
+			);
+
+		this.isLoading(true);
+		this.isError(false);
+		this.errorMessage("");
+	};
+
+	// Returns message of the most recent error of update stages which are still failing.
+	const lastWorkerError = function(worker) {
+		let result = null;
+		let time = null;
+
+		for (const stage in worker.stages) {
+			const status = worker.stages[stage];
+
+			if (status.consecutive_failures > 0 && (time === null || status.last_error_time > time)) {
+				result = stage + ": " + status.last_error;
+				time = status.last_error_time;
+			}
+		}
+
+		return result;
+	};
+
 	Application.prototype.updateState = function() {
 		reqwest({
 			url: "/api/v1/state",
 			type: "json",
 			method: "POST",
+			contentType: "application/json",
+			data: JSON.stringify({
+				server: this.selectedServer(),
+			}),
 		})
 			.then(
 				function(resp) {
@@ -353,6 +474,9 @@ define(["knockout", "reqwest", "moment", "vega", "vega-embed", "Database", "Util
 						this.diskCapacity(resp.result.disk_capacity);
 						this.softThreshold(resp.result.soft_threshold);
 						this.hardThreshold(resp.result.hard_threshold);
+						this.isStale(resp.result.stale);
+						this.updated(resp.result.updated);
+						this.workerError(lastWorkerError(resp.result.worker));
 						this.databases(
 							resp.result.databases.map(function(database) {
 								return new Database(database);
@@ -5,8 +5,25 @@ define(["knockout", "moment", "Util"], function(ko, moment, Util) {
 		this.service = ko.observable(params["service"]);
 		this.name = ko.observable(params["name"]);
 		this.user = ko.observable(params["user"] || "\u2014");
+		this.attribution = ko.observable(params["attribution"] || "");
+		this.owner = ko.observable(params["owner"]);
+		this.comment = ko.observable(params["comment"] || "");
+		this.sessions = ko.observable(params["sessions"] || {});
+
+		this.sessionCount = ko.pureComputed(function() {
+			const sessions = this.sessions();
+
+			return (sessions.active || 0) + (sessions.idle || 0) + (sessions.idle_in_transaction || 0);
+		}, this);
+
+		this.hasSessions = ko.pureComputed(function() {
+			return this.sessionCount() > 0;
+		}, this);
 		this.modified = ko.observable(params["modified"]);
+		this.lastActive = ko.observable(params["last_active"] || 0);
 		this.size = ko.observable(params["size"]);
+		this.reclaimable = ko.observable(params["reclaimable"]);
+		this.overQuota = ko.observable(params["over_quota"]);
 
 		this.isService = ko.pureComputed(function() {
 			return this.service();
@@ -20,6 +37,14 @@ define(["knockout", "moment", "Util"], function(ko, moment, Util) {
 			return Util.humanSize(this.size());
 		}, this);
 
+		this.reclaimableHuman = ko.pureComputed(function() {
+			if (this.reclaimable() == null) {
+				return null;
+			}
+
+			return "reclaimable " + Util.humanSize(this.reclaimable());
+		}, this);
+
 		this.modifiedFormat = ko.pureComputed(function() {
 			return moment.unix(this.modified()).format("YYYY.MM.DD HH:mm");
 		}, this);
@@ -27,6 +52,14 @@ define(["knockout", "moment", "Util"], function(ko, moment, Util) {
 		this.modifiedFromNow = ko.pureComputed(function() {
 			return moment.unix(this.modified()).fromNow(false);
 		}, this);
+
+		this.lastActiveFromNow = ko.pureComputed(function() {
+			if (this.lastActive() === 0) {
+				return "\u2014";
+			}
+
+			return moment.unix(this.lastActive()).fromNow(false);
+		}, this);
 	};
 
 	return Database;
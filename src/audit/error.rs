@@ -0,0 +1,44 @@
+use serde_json::Error as JsonError;
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::io::Error as IoError;
+
+pub type AuditResult<T> = Result<T, AuditError>;
+
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum AuditError {
+    IoError { message: String },
+    FormatError { message: String },
+    LockError,
+}
+
+impl AuditError {
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn io_error(error: IoError) -> AuditError {
+        AuditError::IoError {
+            message: format!("{}", error),
+        }
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn format_error(error: JsonError) -> AuditError {
+        AuditError::FormatError {
+            message: format!("{}", error),
+        }
+    }
+}
+
+impl Error for AuditError {}
+
+impl Display for AuditError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            AuditError::IoError { message } => write!(f, "{}", message),
+            AuditError::FormatError { message } => write!(f, "{}", message),
+            AuditError::LockError => write!(f, "Failed to acquire audit lock"),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+mod error;
+mod record;
+
+pub use self::error::AuditError;
+pub use self::error::AuditResult;
+pub use self::record::Record;
+
+use crate::clock;
+use crate::config::AuditConfig;
+use serde_json::Value;
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone)]
+pub struct AuditRef {
+    inner: Arc<Mutex<AuditLog>>,
+}
+
+impl AuditRef {
+    fn with_log<F, T>(&self, callback: F) -> AuditResult<T>
+    where
+        F: FnOnce(&mut AuditLog) -> AuditResult<T>,
+    {
+        match self.inner.lock() {
+            Ok(ref mut log) => callback(log),
+            Err(err) => {
+                warn!("Failed to acquire audit lock - {}", err);
+
+                Err(AuditError::LockError)
+            }
+        }
+    }
+
+    pub fn append(&self, record: &Record) -> AuditResult<()> {
+        self.with_log(move |log| log.append(record))
+    }
+
+    /// Append record of drop attempt which was rejected before it was processed, errors are only logged. Server and
+    /// database are taken from JSON body of the request when possible.
+    pub fn append_rejected(
+        &self,
+        remote_address: &str,
+        user: Option<&str>,
+        body: &str,
+        message: &str,
+    ) {
+        let value: Value = serde_json::from_str(body).unwrap_or(Value::Null);
+        let field = |name: &str| value.get(name).and_then(Value::as_str).unwrap_or_default();
+        let record = Record::new(
+            clock::unix_timestamp(),
+            remote_address,
+            user,
+            field("server"),
+            field("name"),
+        )
+        .with_outcome(Some(message.into()));
+
+        if let Err(err) = self.append(&record) {
+            error!("Failed to write audit record - {}", err);
+        }
+    }
+
+    /// Returns total number of records and a page of records ordered from newest to oldest.
+    pub fn page(&self, offset: usize, limit: usize) -> AuditResult<(usize, Vec<Record>)> {
+        self.with_log(move |log| log.page(offset, limit))
+    }
+}
+
+#[derive(Debug)]
+struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    fn open(config: &AuditConfig) -> AuditResult<AuditLog> {
+        if let Some(parent) = config.path().parent() {
+            fs::create_dir_all(parent).map_err(AuditError::io_error)?;
+        }
+
+        Ok(AuditLog {
+            path: config.path().into(),
+        })
+    }
+
+    fn append(&mut self, record: &Record) -> AuditResult<()> {
+        let mut line = serde_json::to_string(record).map_err(AuditError::format_error)?;
+        line.push('\n');
+
+        // Every record written by single call, so partially written records are not possible.
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(AuditError::io_error)
+    }
+
+    fn page(&self, offset: usize, limit: usize) -> AuditResult<(usize, Vec<Record>)> {
+        if !self.path.exists() {
+            return Ok((0, Vec::new()));
+        }
+
+        let reader = BufReader::new(File::open(&self.path).map_err(AuditError::io_error)?);
+        let mut records = Vec::new();
+
+        for line in reader.lines() {
+            let line = line.map_err(AuditError::io_error)?;
+
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(err) => warn!("Skip invalid audit record - {}", err),
+            }
+        }
+
+        let total = records.len();
+        let page = records.into_iter().rev().skip(offset).take(limit).collect();
+
+        Ok((total, page))
+    }
+}
+
+pub fn open(config: &AuditConfig) -> AuditResult<AuditRef> {
+    Ok(AuditRef {
+        inner: Arc::new(Mutex::new(AuditLog::open(config)?)),
+    })
+}
@@ -0,0 +1,57 @@
+use crate::state::Database;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    time: i64,
+    remote_address: String,
+    user: Option<String>,
+    server: String,
+    database: String,
+    size: Option<u64>,
+    owner: Option<String>,
+    attributed_user: Option<String>,
+    success: bool,
+    message: Option<String>,
+}
+
+impl Record {
+    pub fn new(
+        time: i64,
+        remote_address: &str,
+        user: Option<&str>,
+        server: &str,
+        database: &str,
+    ) -> Record {
+        Record {
+            time,
+            remote_address: remote_address.into(),
+            user: user.map(String::from),
+            server: server.into(),
+            database: database.into(),
+            size: None,
+            owner: None,
+            attributed_user: None,
+            success: false,
+            message: None,
+        }
+    }
+
+    /// Set database size, owner and user known at the time of the action.
+    pub fn with_database(mut self, database: Option<&Database>) -> Record {
+        if let Some(database) = database {
+            self.size = Some(database.size());
+            self.owner = Some(database.info().owner().into());
+            self.attributed_user = database.user().cloned();
+        }
+
+        self
+    }
+
+    /// Set outcome of the action. Error message must be `None` if action completed successfully.
+    pub fn with_outcome(mut self, error_message: Option<String>) -> Record {
+        self.success = error_message.is_none();
+        self.message = error_message;
+
+        self
+    }
+}
@@ -0,0 +1,25 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+#[derive(Debug)]
+pub struct AuthError {
+    message: String,
+}
+
+impl AuthError {
+    pub fn new(message: &str) -> AuthError {
+        AuthError {
+            message: message.into(),
+        }
+    }
+}
+
+impl Error for AuthError {}
+
+impl Display for AuthError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}", self.message)
+    }
+}
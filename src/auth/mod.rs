@@ -0,0 +1,195 @@
+mod error;
+mod password;
+
+pub use self::error::AuthError;
+pub use self::password::hash_iterations;
+pub use self::password::is_valid_hash;
+
+use self::password::constant_time_eq;
+use self::password::PasswordVerifier;
+use crate::audit::AuditRef;
+use crate::config::ConfigRef;
+use crate::config::SharedConfig;
+use iron::headers::Authorization;
+use iron::headers::Basic;
+use iron::headers::Bearer;
+use iron::status;
+use iron::typemap::Key;
+use iron::BeforeMiddleware;
+use iron::IronError;
+use iron::IronResult;
+use iron::Request;
+use iron::Response;
+use std::io::Read;
+
+const DEFAULT_REALM: &str = "postgres-status";
+const DROPDB_PATH: [&str; 3] = ["api", "v1", "dropdb"];
+
+/// Authenticated user. Middleware stores it in request extensions if request contains valid credentials.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    login: String,
+    admin: bool,
+}
+
+impl AuthUser {
+    pub fn login(&self) -> &str {
+        &self.login
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.admin
+    }
+}
+
+impl Key for AuthUser {
+    type Value = AuthUser;
+}
+
+#[derive(Debug)]
+pub struct AuthMiddleware {
+    config: SharedConfig,
+    audit: Option<AuditRef>,
+    verifier: PasswordVerifier,
+}
+
+impl AuthMiddleware {
+    pub fn new(config: SharedConfig, audit: Option<AuditRef>) -> AuthMiddleware {
+        AuthMiddleware {
+            config,
+            audit,
+            verifier: PasswordVerifier::default(),
+        }
+    }
+
+    /// Audit drop database request rejected because of invalid credentials. Handler will not be called for it.
+    fn audit_rejected(&self, request: &mut Request, err: &AuthError) {
+        let audit = match self.audit {
+            Some(ref audit) if is_dropdb_path(&request.url.path()) => audit,
+            _ => return,
+        };
+        let mut body = String::new();
+
+        if let Err(err) = request.body.read_to_string(&mut body) {
+            warn!("Failed to read request body - {}", err);
+        }
+
+        audit.append_rejected(
+            &request.remote_addr.ip().to_string(),
+            None,
+            &body,
+            &format!("Authentication failed - {}", err),
+        );
+    }
+
+    fn authenticate(
+        &self,
+        config: &ConfigRef,
+        request: &Request,
+    ) -> Result<Option<AuthUser>, AuthError> {
+        let users = config.users();
+
+        if let Some(Authorization(Basic { username, password })) =
+            request.headers.get::<Authorization<Basic>>()
+        {
+            let password = password.as_ref().map_or("", String::as_str);
+            let user = users
+                .iter()
+                .find(|user| user.login().eq_ignore_ascii_case(username))
+                .filter(|user| {
+                    user.password_hash()
+                        .is_some_and(|hash| self.verifier.verify(hash, password))
+                })
+                .ok_or_else(|| AuthError::new("Invalid login or password"))?;
+
+            Ok(Some(AuthUser {
+                login: user.login().to_lowercase(),
+                admin: user.is_admin(),
+            }))
+        } else if let Some(Authorization(Bearer { token })) =
+            request.headers.get::<Authorization<Bearer>>()
+        {
+            let user = users
+                .iter()
+                .find(|user| {
+                    user.tokens()
+                        .iter()
+                        .any(|expected| constant_time_eq(expected.as_bytes(), token.as_bytes()))
+                })
+                .ok_or_else(|| AuthError::new("Invalid token"))?;
+
+            Ok(Some(AuthUser {
+                login: user.login().to_lowercase(),
+                admin: user.is_admin(),
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl BeforeMiddleware for AuthMiddleware {
+    fn before(&self, request: &mut Request) -> IronResult<()> {
+        let config = self.config.get();
+
+        match self.authenticate(&config, request) {
+            Ok(Some(user)) => {
+                request.extensions.insert::<AuthUser>(user);
+
+                Ok(())
+            }
+            Ok(None) => Ok(()),
+            Err(err) => {
+                warn!("Authentication failed - {}", err);
+                self.audit_rejected(request, &err);
+
+                Err(IronError {
+                    error: Box::new(err),
+                    response: unauthorized(&config),
+                })
+            }
+        }
+    }
+}
+
+/// Returns `true` if authentication is not configured or user is administrator. Only such users can see addresses of
+/// other users.
+pub fn is_privileged(config: &ConfigRef, user: Option<&AuthUser>) -> bool {
+    config.authentication().is_none() || user.is_some_and(AuthUser::is_admin)
+}
+
+/// Create response which asks client to authenticate.
+pub fn unauthorized(config: &ConfigRef) -> Response {
+    let realm = config
+        .authentication()
+        .and_then(|authentication| authentication.realm())
+        .unwrap_or(DEFAULT_REALM);
+    let mut response = Response::with((status::Unauthorized, "Authentication required"));
+    response.headers.set_raw(
+        "WWW-Authenticate",
+        vec![format!("Basic realm=\"{}\"", realm).into_bytes()],
+    );
+
+    response
+}
+
+/// Checks that request path is drop path, trailing slash is ignored like by router.
+fn is_dropdb_path(path: &[&str]) -> bool {
+    match path.split_last() {
+        Some((&"", rest)) => rest == DROPDB_PATH,
+        _ => path == DROPDB_PATH,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_dropdb_path_ignores_trailing_slash() {
+        assert!(is_dropdb_path(&["api", "v1", "dropdb"]));
+        assert!(is_dropdb_path(&["api", "v1", "dropdb", ""]));
+        assert!(!is_dropdb_path(&["api", "v1", "dropdb", "x"]));
+        assert!(!is_dropdb_path(&["api", "v1", "state"]));
+    }
+}
@@ -0,0 +1,193 @@
+use sha2::Digest;
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+
+const PBKDF2_PREFIX: &str = "pbkdf2-sha256";
+const DIGEST_LENGTH: usize = 32;
+const VERIFIED_CACHE_SIZE: usize = 1024;
+
+/// Password verifier which remembers successfully verified passwords. Browsers send credentials with every request, so
+/// slow key derivation runs only once for every password hash and password pair.
+#[derive(Debug, Default)]
+pub struct PasswordVerifier {
+    verified: Mutex<HashSet<Vec<u8>>>,
+}
+
+impl PasswordVerifier {
+    pub fn verify(&self, hash: &str, password: &str) -> bool {
+        // Only fast digest of the pair is kept, so passwords are not stored in memory.
+        let key = Sha256::new()
+            .chain_update(hash.as_bytes())
+            .chain_update([0])
+            .chain_update(password.as_bytes())
+            .finalize()
+            .to_vec();
+
+        if self.lock().contains(&key) {
+            return true;
+        }
+
+        if !verify_password(hash, password) {
+            return false;
+        }
+
+        let mut verified = self.lock();
+
+        if verified.len() >= VERIFIED_CACHE_SIZE {
+            verified.clear();
+        }
+
+        verified.insert(key);
+
+        true
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashSet<Vec<u8>>> {
+        self.verified.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Checks that password hash has format `pbkdf2-sha256:ITERATIONS:SALT:HEX_DIGEST`.
+pub fn is_valid_hash(hash: &str) -> bool {
+    match split_hash(hash) {
+        Some((_, _, digest)) => {
+            digest.len() == DIGEST_LENGTH * 2 && digest.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => false,
+    }
+}
+
+/// Returns number of PBKDF2 iterations of valid password hash.
+pub fn hash_iterations(hash: &str) -> Option<u32> {
+    split_hash(hash).map(|(iterations, _, _)| iterations)
+}
+
+/// Verify password against hash in format `pbkdf2-sha256:ITERATIONS:SALT:HEX_DIGEST`, where digest is 32 bytes of
+/// PBKDF2-HMAC-SHA256 of password with given salt and number of iterations.
+pub fn verify_password(hash: &str, password: &str) -> bool {
+    match split_hash(hash) {
+        Some((iterations, salt, digest)) => {
+            let mut derived = [0u8; DIGEST_LENGTH];
+
+            pbkdf2::pbkdf2_hmac::<Sha256>(
+                password.as_bytes(),
+                salt.as_bytes(),
+                iterations,
+                &mut derived,
+            );
+
+            let actual: String = derived.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+            constant_time_eq(actual.as_bytes(), digest.to_lowercase().as_bytes())
+        }
+        None => false,
+    }
+}
+
+/// Compare two byte strings in time independent of their content.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn split_hash(hash: &str) -> Option<(u32, &str, &str)> {
+    let mut parts = hash.splitn(4, ':');
+
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(PBKDF2_PREFIX), Some(iterations), Some(salt), Some(digest)) => {
+            match iterations.parse() {
+                Ok(iterations) if iterations > 0 => Some((iterations, salt, digest)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors of PBKDF2-HMAC-SHA256 with password `password` and salt `salt`.
+    const ONE_ITERATION: &str =
+        "pbkdf2-sha256:1:salt:120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b";
+    const TWO_ITERATIONS: &str =
+        "pbkdf2-sha256:2:salt:ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43";
+
+    #[test]
+    fn verify_password_accepts_matching_password() {
+        assert!(verify_password(ONE_ITERATION, "password"));
+        assert!(verify_password(TWO_ITERATIONS, "password"));
+    }
+
+    #[test]
+    fn verify_password_accepts_uppercase_digest() {
+        let hash = ONE_ITERATION.replace("120fb6cffcf8b32c", "120FB6CFFCF8B32C");
+
+        assert!(verify_password(&hash, "password"));
+    }
+
+    #[test]
+    fn verify_password_rejects_wrong_password() {
+        assert!(!verify_password(ONE_ITERATION, "Password"));
+        assert!(!verify_password(ONE_ITERATION, ""));
+    }
+
+    #[test]
+    fn verify_password_uses_iterations_and_salt() {
+        assert!(!verify_password(
+            &ONE_ITERATION.replace(":1:", ":2:"),
+            "password"
+        ));
+        assert!(!verify_password(
+            &ONE_ITERATION.replace(":salt:", ":pepper:"),
+            "password"
+        ));
+    }
+
+    #[test]
+    fn verify_password_rejects_invalid_hash() {
+        assert!(!verify_password("", "password"));
+        assert!(!verify_password(
+            &ONE_ITERATION.replace(":1:", ":0:"),
+            "password"
+        ));
+        assert!(!verify_password(
+            "sha256:salt:7a37b85c8918eac19a9089c0fa5a2ab4dce3f90528dcdeec108b23ddf3607b99",
+            "password"
+        ));
+    }
+
+    #[test]
+    fn password_verifier_remembers_only_valid_passwords() {
+        let verifier = PasswordVerifier::default();
+
+        assert!(!verifier.verify(ONE_ITERATION, "wrong"));
+        assert!(verifier.verify(ONE_ITERATION, "password"));
+        assert!(verifier.verify(ONE_ITERATION, "password"));
+        assert!(!verifier.verify(ONE_ITERATION, "wrong"));
+        assert!(!verifier.verify(TWO_ITERATIONS, "wrong"));
+        assert_eq!(verifier.lock().len(), 1);
+    }
+
+    #[test]
+    fn is_valid_hash_checks_format() {
+        assert!(is_valid_hash(ONE_ITERATION));
+        assert!(!is_valid_hash("pbkdf2-sha256:1:salt:120fb6"));
+        assert!(!is_valid_hash(&ONE_ITERATION.replace(":1:", ":many:")));
+        assert!(!is_valid_hash(&ONE_ITERATION.replace("120f", "zz0f")));
+        assert!(!is_valid_hash(&ONE_ITERATION.replace(":salt:", ":sa:lt:")));
+    }
+
+    #[test]
+    fn hash_iterations_returns_cost() {
+        assert_eq!(hash_iterations(TWO_ITERATIONS), Some(2));
+        assert_eq!(hash_iterations("sha256:salt:00"), None);
+    }
+}
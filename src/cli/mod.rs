@@ -0,0 +1,394 @@
+use crate::audit;
+use crate::audit::Record;
+use crate::clock;
+use crate::config::ConfigRef;
+use crate::config::DiskConfig;
+use crate::config::ServerConfig;
+use crate::error::ApplicationError;
+use crate::error::ApplicationResult;
+use crate::handler;
+use crate::handler::StateData;
+use crate::options::Command;
+use crate::options::Format;
+use crate::postgres;
+use crate::postgres::DatabaseError;
+use crate::state;
+use crate::state::Mount;
+use crate::state::StateRef;
+use crate::table::human_size;
+use crate::table::Table;
+use crate::worker;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::io::Error as IoError;
+use std::io::ErrorKind;
+use std::io::Result as IoResult;
+use std::io::StdoutLock;
+use std::io::Write;
+
+const REMOTE_ADDRESS: &str = "command line";
+
+/// Execute command which does not start web server. Servers are queried directly, running instance of the application
+/// is not required.
+pub fn run(command: &Command, config: ConfigRef) -> ApplicationResult {
+    let state = state::create();
+
+    match command {
+        Command::Serve => Ok(()),
+        Command::List { server, format } => list(&config, &state, server.as_deref(), *format),
+        Command::Drop {
+            name,
+            server,
+            force,
+        } => drop(&config, &state, name, server.as_deref(), *force),
+        Command::Disk { server, format } => disk(&config, &state, server.as_deref(), *format),
+    }
+}
+
+/// Print databases of every selected server in the same form as state API returns them.
+fn list(
+    config: &ConfigRef,
+    state: &StateRef,
+    server: Option<&str>,
+    format: Format,
+) -> ApplicationResult {
+    let postgres = postgres::create();
+    let mut states = BTreeMap::new();
+
+    load_activity(config, state);
+
+    for server_config in select_servers(config, server)? {
+        let name = server_config.name();
+
+        worker::update_disk(server_config, state).map_err(|err| {
+            ApplicationError::command_error(&format!(
+                "Failed to update disk state of {} - {}",
+                name, err
+            ))
+        })?;
+        worker::update_databases(
+            config,
+            server_config,
+            &postgres.server(server_config),
+            state,
+        )
+        .map_err(|err| {
+            ApplicationError::command_error(&format!(
+                "Failed to query databases of {} - {}",
+                name, err
+            ))
+        })?;
+
+        let data = handler::server_state(config, state, server_config)
+            .map_err(|err| ApplicationError::command_error(&format!("{}", err)))?;
+
+        states.insert(name, data);
+    }
+
+    if format == Format::Json {
+        return print_json(&states);
+    }
+
+    print_table(&database_table(&states, format == Format::Table), format)
+}
+
+fn database_table(states: &BTreeMap<&str, StateData>, human: bool) -> Table {
+    let mut table = Table::new(vec![
+        "server",
+        "name",
+        "user",
+        "owner",
+        "size",
+        "modified",
+        "last_active",
+        "service",
+        "over_quota",
+    ]);
+    let timestamp = |timestamp: i64| {
+        if human {
+            clock::format_timestamp(timestamp)
+        } else {
+            timestamp.to_string()
+        }
+    };
+
+    for (server, data) in states {
+        for database in data.databases() {
+            table.add_row(vec![
+                server.to_string(),
+                database.name().into(),
+                database.user().unwrap_or_default().into(),
+                database.owner().into(),
+                if human {
+                    human_size(database.size())
+                } else {
+                    database.size().to_string()
+                },
+                timestamp(database.modified()),
+                database.last_active().map(timestamp).unwrap_or_default(),
+                database.service().to_string(),
+                database.over_quota().to_string(),
+            ]);
+        }
+    }
+
+    table
+}
+
+/// Drop database with the same checks as drop API uses. Action is written to audit log if it is configured.
+fn drop(
+    config: &ConfigRef,
+    state: &StateRef,
+    name: &str,
+    server: Option<&str>,
+    force: bool,
+) -> ApplicationResult {
+    let server_config = match server {
+        Some(server) => find_server(config, server)?,
+        None if config.servers().len() == 1 => &config.servers()[0],
+        None => {
+            return Err(ApplicationError::command_error(
+                "Several servers are configured, select server of the database with --server",
+            ))
+        }
+    };
+    let postgres = postgres::create().server(server_config);
+
+    load_activity(config, state);
+
+    // State is used only to record size and attribution of the database in audit log.
+    if let Err(err) = worker::update_databases(config, server_config, &postgres, state) {
+        warn!(
+            "Failed to query databases of {} - {}",
+            server_config.name(),
+            err
+        );
+    }
+
+    let database = state.database(server_config.name(), name).ok().flatten();
+    let result = postgres.drop_database(
+        name,
+        server_config.service_databases(),
+        server_config.drop_requires_force(),
+        force,
+    );
+
+    if let Some(audit_config) = config.audit() {
+        let record = Record::new(
+            clock::unix_timestamp(),
+            REMOTE_ADDRESS,
+            None,
+            server_config.name(),
+            name,
+        )
+        .with_database(database.as_ref())
+        .with_outcome(result.as_ref().err().map(|err| format!("{}", err)));
+
+        match audit::open(audit_config) {
+            Ok(audit) => {
+                if let Err(err) = audit.append(&record) {
+                    error!("Failed to write audit record - {}", err);
+                }
+            }
+            Err(err) => error!("Failed to open audit log - {}", err),
+        }
+    }
+
+    match result {
+        Ok(()) => {
+            println!("Database {} dropped on {}", name, server_config.name());
+
+            Ok(())
+        }
+        Err(err) => {
+            if let DatabaseError::ActiveSessions { ref sessions } = err {
+                for session in sessions {
+                    println!("{}", serde_json::to_string(session).unwrap_or_default());
+                }
+            }
+
+            Err(ApplicationError::command_error(&format!(
+                "Failed to drop database `{}` - {}",
+                name, err
+            )))
+        }
+    }
+}
+
+/// Update disk state of every selected server once and print it.
+fn disk(
+    config: &ConfigRef,
+    state: &StateRef,
+    server: Option<&str>,
+    format: Format,
+) -> ApplicationResult {
+    let postgres = postgres::create();
+    let mut disks = BTreeMap::new();
+
+    for server_config in select_servers(config, server)? {
+        worker::update_disk(server_config, state).map_err(|err| {
+            ApplicationError::command_error(&format!(
+                "Failed to update disk state of {} - {}",
+                server_config.name(),
+                err
+            ))
+        })?;
+
+        // Usage of fixed disk is calculated from sizes of databases.
+        if let DiskConfig::Fixed { .. } = server_config.disk() {
+            worker::update_databases(
+                config,
+                server_config,
+                &postgres.server(server_config),
+                state,
+            )
+            .map_err(|err| {
+                ApplicationError::command_error(&format!(
+                    "Failed to query databases of {} - {}",
+                    server_config.name(),
+                    err
+                ))
+            })?;
+        }
+
+        let disk = state
+            .disk_state(server_config.name())
+            .map_err(|err| ApplicationError::command_error(&format!("{}", err)))?;
+        let used = state
+            .disk_used(server_config)
+            .map_err(|err| ApplicationError::command_error(&format!("{}", err)))?;
+
+        disks.insert(
+            server_config.name(),
+            DiskData {
+                offset: disk.offset(),
+                capacity: disk.capacity(),
+                soft_threshold: disk.soft_threshold(),
+                hard_threshold: disk.hard_threshold(),
+                used,
+                mounts: disk.mounts().to_vec(),
+            },
+        );
+    }
+
+    if format == Format::Json {
+        return print_json(&disks);
+    }
+
+    print_table(&disk_table(&disks, format == Format::Table), format)
+}
+
+fn disk_table(disks: &BTreeMap<&str, DiskData>, human: bool) -> Table {
+    let mut table = Table::new(vec![
+        "server",
+        "mount",
+        "capacity",
+        "used",
+        "offset",
+        "soft_threshold",
+        "hard_threshold",
+    ]);
+    let size = |value: u64| {
+        if human {
+            human_size(value)
+        } else {
+            value.to_string()
+        }
+    };
+
+    for (server, disk) in disks {
+        table.add_row(vec![
+            server.to_string(),
+            String::new(),
+            size(disk.capacity),
+            size(disk.used),
+            size(disk.offset),
+            size(disk.soft_threshold),
+            size(disk.hard_threshold),
+        ]);
+
+        for mount in &disk.mounts {
+            table.add_row(vec![
+                server.to_string(),
+                mount.path().into(),
+                size(mount.capacity()),
+                mount.used().map(size).unwrap_or_default(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ]);
+        }
+    }
+
+    table
+}
+
+/// Returns all servers or the only server with given name.
+fn select_servers<'a>(
+    config: &'a ConfigRef,
+    server: Option<&str>,
+) -> Result<Vec<&'a ServerConfig>, ApplicationError> {
+    match server {
+        Some(server) => Ok(vec![find_server(config, server)?]),
+        None => Ok(config.servers().iter().collect()),
+    }
+}
+
+fn find_server<'a>(
+    config: &'a ConfigRef,
+    name: &str,
+) -> Result<&'a ServerConfig, ApplicationError> {
+    config
+        .server(name)
+        .ok_or_else(|| ApplicationError::command_error(&format!("Unknown server {}", name)))
+}
+
+/// Load stored activity, so last activity time is known. Activity is never saved by commands, file belongs to the server.
+fn load_activity(config: &ConfigRef, state: &StateRef) {
+    if let Some(activity) = config.activity() {
+        if let Err(err) = state.load_activity(activity.path()) {
+            warn!("Failed to load database activity: {}", err);
+        }
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> ApplicationResult {
+    print(|writer| {
+        serde_json::to_writer_pretty(&mut *writer, value).map_err(IoError::from)?;
+        writeln!(writer)
+    })
+}
+
+fn print_table(table: &Table, format: Format) -> ApplicationResult {
+    print(|writer| match format {
+        Format::Csv => table.write_csv(writer),
+        _ => table.write_text(writer),
+    })
+}
+
+/// Write output to stdout. Closed stdout is not an error, so output can be piped to `head`.
+fn print<F>(write: F) -> ApplicationResult
+where
+    F: FnOnce(&mut StdoutLock) -> IoResult<()>,
+{
+    let stdout = io::stdout();
+    let mut writer = stdout.lock();
+
+    match write(&mut writer).and_then(|_| writer.flush()) {
+        Err(err) if err.kind() != ErrorKind::BrokenPipe => Err(ApplicationError::command_error(
+            &format!("Failed to write output - {}", err),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct DiskData {
+    offset: u64,
+    capacity: u64,
+    soft_threshold: u64,
+    hard_threshold: u64,
+    used: u64,
+    mounts: Vec<Mount>,
+}
@@ -0,0 +1,19 @@
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use time::OffsetDateTime;
+
+/// Returns current time as number of seconds since UNIX epoch.
+pub fn unix_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Format UNIX timestamp as UTC date and time with minute precision.
+pub fn format_timestamp(timestamp: i64) -> String {
+    match OffsetDateTime::from_unix_timestamp(timestamp) {
+        Ok(time) => format!("{} {:02}:{:02}", time.date(), time.hour(), time.minute()),
+        Err(_) => timestamp.to_string(),
+    }
+}
@@ -1,12 +1,23 @@
 mod error;
+mod pattern;
+mod reload;
+mod secrets;
 mod validate;
 
 pub use self::error::ConfigError;
 pub use self::error::ConfigResult;
+pub use self::pattern::Pattern;
+pub use self::reload::watch;
+pub use self::reload::SharedConfig;
+pub use self::validate::check;
 pub use self::validate::validate;
+pub use self::validate::Severity;
+use serde_yaml::Value;
+use std::collections::HashMap;
 use std::collections::HashSet;
-use std::fs::File;
+use std::fs;
 use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 pub type ConfigRef = Arc<Config>;
@@ -14,9 +25,27 @@ pub type ConfigRef = Arc<Config>;
 #[derive(Debug, Deserialize)]
 pub struct Config {
     update_interval: u64,
-    server: ServerConfig,
+    #[serde(default)]
+    server: Option<ServerConfig>,
+    #[serde(default)]
+    servers: Vec<ServerConfig>,
     cors: Option<Cors>,
     users: Vec<UserConfig>,
+    history: Option<HistoryConfig>,
+    authentication: Option<AuthConfig>,
+    audit: Option<AuditConfig>,
+    retention: Option<RetentionConfig>,
+    attribution: Option<AttributionConfig>,
+    activity: Option<ActivityConfig>,
+    bloat: Option<BloatConfig>,
+    https: Option<HttpsConfig>,
+    health: Option<HealthConfig>,
+    /// Text of configuration file used to find lines of validation issues.
+    #[serde(skip)]
+    source: String,
+    /// Set if the first server is defined by `server` section.
+    #[serde(skip)]
+    single_server: bool,
 }
 
 impl Config {
@@ -24,8 +53,12 @@ impl Config {
         self.update_interval
     }
 
-    pub fn server(&self) -> &ServerConfig {
-        &self.server
+    pub fn servers(&self) -> &[ServerConfig] {
+        &self.servers
+    }
+
+    pub fn server(&self, name: &str) -> Option<&ServerConfig> {
+        self.servers.iter().find(|server| server.name() == name)
     }
 
     pub fn cors(&self) -> Option<&Cors> {
@@ -35,19 +68,102 @@ impl Config {
     pub fn users(&self) -> &[UserConfig] {
         &self.users
     }
+
+    pub fn history(&self) -> Option<&HistoryConfig> {
+        self.history.as_ref()
+    }
+
+    pub fn authentication(&self) -> Option<&AuthConfig> {
+        self.authentication.as_ref()
+    }
+
+    pub fn audit(&self) -> Option<&AuditConfig> {
+        self.audit.as_ref()
+    }
+
+    pub fn retention(&self) -> Option<&RetentionConfig> {
+        self.retention.as_ref()
+    }
+
+    pub fn activity(&self) -> Option<&ActivityConfig> {
+        self.activity.as_ref()
+    }
+
+    pub fn https(&self) -> Option<&HttpsConfig> {
+        self.https.as_ref()
+    }
+
+    pub fn bloat(&self) -> Option<&BloatConfig> {
+        self.bloat.as_ref()
+    }
+
+    pub fn health(&self) -> Option<&HealthConfig> {
+        self.health.as_ref()
+    }
+
+    /// Returns YAML path of server with given index.
+    pub fn server_path(&self, index: usize) -> String {
+        match (self.single_server, index) {
+            (true, 0) => "server".into(),
+            (true, index) => format!("servers[{}]", index - 1),
+            (false, index) => format!("servers[{}]", index),
+        }
+    }
+
+    /// Returns number of seconds after which server data is considered stale. Defaults to three update intervals.
+    pub fn stale_after(&self) -> u64 {
+        match self.health {
+            Some(ref health) => health.stale_after,
+            None => self.update_interval * 3,
+        }
+    }
+
+    /// Returns database attribution rules in order of precedence. If attribution is not configured, databases are
+    /// attributed to users whose login is contained in database name.
+    pub fn attribution_rules(&self) -> &[AttributionRule] {
+        match self.attribution {
+            Some(ref attribution) => &attribution.rules,
+            None => DEFAULT_ATTRIBUTION_RULES,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ServerConfig {
+    name: Option<String>,
+    #[serde(default, deserialize_with = "secrets::scalar_string")]
     host: String,
+    #[serde(default)]
     port: u16,
+    #[serde(default, deserialize_with = "secrets::scalar_string")]
     role: String,
+    #[serde(default, deserialize_with = "secrets::scalar_string")]
     password: String,
+    password_file: Option<PathBuf>,
+    password_env: Option<String>,
     service_databases: HashSet<String>,
     disk: DiskConfig,
+    #[serde(default)]
+    drop_requires_force: bool,
+    #[serde(default)]
+    ssl_mode: SslMode,
+    ssl_root_cert: Option<PathBuf>,
+    ssl_cert: Option<PathBuf>,
+    ssl_key: Option<PathBuf>,
+    #[serde(default)]
+    pool: PoolConfig,
+    #[serde(default = "default_connect_timeout")]
+    connect_timeout: u64,
+    #[serde(default = "default_statement_timeout")]
+    statement_timeout: u64,
 }
 
 impl ServerConfig {
+    /// Returns server identifier. If name is not set in configuration, host name will be used instead.
+    pub fn name(&self) -> &str {
+        self.name.as_ref().unwrap_or(&self.host)
+    }
+
     pub fn disk(&self) -> &DiskConfig {
         &self.disk
     }
@@ -71,6 +187,113 @@ impl ServerConfig {
     pub fn service_databases(&self) -> &HashSet<String> {
         &self.service_databases
     }
+
+    /// Returns true if databases with active sessions must be dropped only with `force` flag.
+    pub fn drop_requires_force(&self) -> bool {
+        self.drop_requires_force
+    }
+
+    pub fn ssl_mode(&self) -> SslMode {
+        self.ssl_mode
+    }
+
+    /// Returns path to PEM bundle of trusted CA certificates.
+    pub fn ssl_root_cert(&self) -> Option<&Path> {
+        self.ssl_root_cert.as_deref()
+    }
+
+    /// Returns path to PEM client certificate.
+    pub fn ssl_cert(&self) -> Option<&Path> {
+        self.ssl_cert.as_deref()
+    }
+
+    /// Returns path to PEM (PKCS #8) private key of client certificate.
+    pub fn ssl_key(&self) -> Option<&Path> {
+        self.ssl_key.as_deref()
+    }
+
+    pub fn pool(&self) -> &PoolConfig {
+        &self.pool
+    }
+
+    /// Returns maximal number of seconds to wait for connection to the server.
+    pub fn connect_timeout(&self) -> u64 {
+        self.connect_timeout
+    }
+
+    /// Returns maximal number of seconds of every query execution.
+    pub fn statement_timeout(&self) -> u64 {
+        self.statement_timeout
+    }
+}
+
+fn default_connect_timeout() -> u64 {
+    10
+}
+
+fn default_statement_timeout() -> u64 {
+    300
+}
+
+fn default_command_timeout() -> u64 {
+    60
+}
+
+fn default_history_retention() -> u64 {
+    // 90 days
+    7_776_000
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PoolConfig {
+    size: u32,
+    idle_timeout: u64,
+    health_check: bool,
+}
+
+impl PoolConfig {
+    /// Returns maximal number of connections to every database of the server.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns number of seconds after which idle connection will be closed.
+    pub fn idle_timeout(&self) -> u64 {
+        self.idle_timeout
+    }
+
+    /// Returns `true` if connection must be checked before it is taken from pool.
+    pub fn health_check(&self) -> bool {
+        self.health_check
+    }
+}
+
+impl Default for PoolConfig {
+    fn default() -> PoolConfig {
+        PoolConfig {
+            size: 4,
+            idle_timeout: 300,
+            health_check: true,
+        }
+    }
+}
+
+/// TLS mode of PostgreSQL connections. Modes have the same meaning as `sslmode` of libpq.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SslMode {
+    /// Do not use TLS.
+    #[default]
+    Disable,
+    /// Use TLS if server supports it, certificate is not verified.
+    Prefer,
+    /// Always use TLS, certificate is verified like in `verify-ca` mode only if root certificates are set.
+    Require,
+    /// Always use TLS and verify that certificate is signed by trusted CA.
+    VerifyCa,
+    /// Always use TLS, verify certificate and that host name matches certificate.
+    VerifyFull,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -84,6 +307,12 @@ pub enum DiskConfig {
     },
     Command {
         command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        env: HashMap<String, String>,
+        #[serde(default = "default_command_timeout")]
+        timeout: u64,
     },
 }
 
@@ -97,20 +326,349 @@ pub enum Cors {
 #[derive(Debug, Clone, Deserialize)]
 pub struct UserConfig {
     login: String,
+    password_hash: Option<String>,
+    #[serde(default)]
+    tokens: Vec<String>,
+    #[serde(default)]
+    admin: bool,
+    quota: Option<QuotaConfig>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    patterns: Vec<Pattern>,
 }
 
 impl UserConfig {
     pub fn login(&self) -> &str {
         self.login.as_ref()
     }
+
+    /// Returns password hash in format `pbkdf2-sha256:ITERATIONS:SALT:HEX_DIGEST`, where digest is PBKDF2-HMAC-SHA256 of
+    /// the password with given salt and number of iterations.
+    pub fn password_hash(&self) -> Option<&str> {
+        self.password_hash.as_deref()
+    }
+
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.admin
+    }
+
+    pub fn quota(&self) -> Option<&QuotaConfig> {
+        self.quota.as_ref()
+    }
+
+    /// Returns additional names used to match database names by prefix, suffix or substring.
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    /// Returns database name patterns used by `UserPattern` attribution rule.
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuotaConfig {
+    size: Option<u64>,
+    soft_size: Option<u64>,
+    databases: Option<u64>,
+}
+
+impl QuotaConfig {
+    /// Returns maximal total size of user databases in bytes.
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// Returns total size of user databases in bytes after which user receives warning.
+    pub fn soft_size(&self) -> Option<u64> {
+        self.soft_size
+    }
+
+    /// Returns maximal number of user databases.
+    pub fn databases(&self) -> Option<u64> {
+        self.databases
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthConfig {
+    realm: Option<String>,
+}
+
+impl AuthConfig {
+    pub fn realm(&self) -> Option<&str> {
+        self.realm.as_deref()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoryConfig {
+    path: PathBuf,
+    #[serde(default = "default_history_retention")]
+    retention: u64,
+    downsample: Option<DownsampleConfig>,
+}
+
+impl HistoryConfig {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns maximal sample age in seconds. Samples older than retention will be removed.
+    pub fn retention(&self) -> u64 {
+        self.retention
+    }
+
+    pub fn downsample(&self) -> Option<&DownsampleConfig> {
+        self.downsample.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DownsampleConfig {
+    after: u64,
+    interval: u64,
+}
+
+impl DownsampleConfig {
+    /// Returns sample age in seconds after which samples will be downsampled.
+    pub fn after(&self) -> u64 {
+        self.after
+    }
+
+    /// Returns interval in seconds. Only one sample per interval will be kept for downsampled series.
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuditConfig {
+    path: PathBuf,
+}
+
+impl AuditConfig {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetentionConfig {
+    #[serde(default = "default_dry_run")]
+    dry_run: bool,
+    rules: Vec<RetentionRule>,
+}
+
+impl RetentionConfig {
+    /// Returns true if candidates must be only reported without dropping.
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn rules(&self) -> &[RetentionRule] {
+        &self.rules
+    }
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum RetentionRule {
+    UserDatabaseAge { days: u64 },
+    PatternAge { pattern: Pattern, days: u64 },
+    DiskHardThreshold,
+}
+
+const DEFAULT_ATTRIBUTION_RULES: &[AttributionRule] = &[AttributionRule::Contains];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AttributionConfig {
+    rules: Vec<AttributionRule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum AttributionRule {
+    Override { databases: HashMap<String, String> },
+    Owner,
+    Comment { pattern: Pattern },
+    Pattern { pattern: Pattern },
+    UserPattern,
+    Prefix,
+    Suffix,
+    Contains,
+}
+
+impl AttributionRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttributionRule::Override { .. } => "override",
+            AttributionRule::Owner => "owner",
+            AttributionRule::Comment { .. } => "comment",
+            AttributionRule::Pattern { .. } => "pattern",
+            AttributionRule::UserPattern => "user_pattern",
+            AttributionRule::Prefix => "prefix",
+            AttributionRule::Suffix => "suffix",
+            AttributionRule::Contains => "contains",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActivityConfig {
+    path: PathBuf,
+}
+
+impl ActivityConfig {
+    /// Returns path to file where database activity is stored between restarts.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BloatConfig {
+    interval: u64,
+    #[serde(default)]
+    pgstattuple: bool,
+}
+
+impl BloatConfig {
+    /// Returns minimal number of seconds between bloat estimations of the same database.
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Returns `true` if `pgstattuple` extension should be used for tables when it is installed.
+    pub fn pgstattuple(&self) -> bool {
+        self.pgstattuple
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HealthConfig {
+    stale_after: u64,
+}
+
+impl HealthConfig {
+    pub fn stale_after(&self) -> u64 {
+        self.stale_after
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpsConfig {
+    cert: PathBuf,
+    key: PathBuf,
+    redirect_port: Option<u16>,
+}
+
+impl HttpsConfig {
+    /// Returns path to PEM certificate chain.
+    pub fn cert(&self) -> &Path {
+        &self.cert
+    }
+
+    /// Returns path to PEM (PKCS #8) private key.
+    pub fn key(&self) -> &Path {
+        &self.key
+    }
+
+    /// Returns port of plain HTTP listener which redirects all requests to HTTPS.
+    pub fn redirect_port(&self) -> Option<u16> {
+        self.redirect_port
+    }
 }
 
 pub fn load<P>(path: P) -> ConfigResult<ConfigRef>
 where
     P: AsRef<Path>,
 {
-    let reader = File::open(path).map_err(ConfigError::io_error)?;
-    let config = serde_yaml::from_reader(reader).map_err(ConfigError::yaml_error)?;
+    let source = fs::read_to_string(path).map_err(ConfigError::io_error)?;
+    let mut config = parse(&source)?;
+
+    config.source = source;
+
+    // Single server configuration is treated as list with one entry.
+    if let Some(server) = config.server.take() {
+        config.servers.insert(0, server);
+        config.single_server = true;
+    }
+
+    for server in &mut config.servers {
+        secrets::resolve(server)?;
+    }
 
     Ok(Arc::new(config))
 }
+
+/// Parse configuration with interpolated references to environment variables.
+fn parse(source: &str) -> ConfigResult<Config> {
+    let mut value: Value = serde_yaml::from_str(source).map_err(ConfigError::yaml_error)?;
+
+    secrets::interpolate(&mut value)?;
+
+    serde_yaml::from_value(value).map_err(|err| {
+        // Errors of deserialization from value have no location. Parsing of the source without interpolation gives
+        // location, but it is used only if it is the same error, interpolated values may fail differently.
+        match serde_yaml::from_str::<Config>(source) {
+            Err(located) if same_error(&located, &err) => ConfigError::yaml_error(located),
+            _ => ConfigError::yaml_error(err),
+        }
+    })
+}
+
+/// Returns `true` if `located` error has location and describes the same failure as `err`. Located error may be
+/// prefixed with path of the failed field.
+fn same_error(located: &serde_yaml::Error, err: &serde_yaml::Error) -> bool {
+    located.location().is_some() && located.to_string().contains(&err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    const SERVER: &str = "server:
+  host: localhost
+  port: 5432
+  role: postgres
+  service_databases: []
+  disk: { command: df }
+";
+
+    fn error(source: &str) -> String {
+        format!("{}", parse(source).unwrap_err())
+    }
+
+    #[test]
+    fn parse_reports_location_of_error() {
+        let message = error(&format!("update_interval: soon\n{}users: []\n", SERVER));
+
+        assert!(message.contains("invalid type"), "{}", message);
+        assert!(message.contains("line 1 column"), "{}", message);
+    }
+
+    #[test]
+    fn parse_reports_error_of_interpolated_config_without_other_location() {
+        env::set_var("CONFIG_TEST_INTERVAL", "60");
+
+        let message = error(&format!(
+            "update_interval: ${{CONFIG_TEST_INTERVAL}}\n{}",
+            SERVER
+        ));
+
+        assert!(message.contains("missing field `users`"), "{}", message);
+        assert!(!message.contains("invalid type"), "{}", message);
+    }
+}
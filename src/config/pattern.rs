@@ -0,0 +1,43 @@
+use regex::Regex;
+use serde::de::Error as DeError;
+use serde::Deserialize;
+use serde::Deserializer;
+
+/// Regular expression which is compiled when configuration is loaded.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    regex: Regex,
+}
+
+impl Pattern {
+    pub fn as_str(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// Returns text of capture group named `user` or the first capture group if the pattern matches.
+    pub fn capture_user<'t>(&self, text: &'t str) -> Option<&'t str> {
+        let captures = self.regex.captures(text)?;
+
+        captures
+            .name("user")
+            .or_else(|| captures.get(1))
+            .map(|capture| capture.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D>(deserializer: D) -> Result<Pattern, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let pattern = String::deserialize(deserializer)?;
+
+        Regex::new(&pattern)
+            .map(|regex| Pattern { regex })
+            .map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,107 @@
+use super::load;
+use super::validate;
+use super::ConfigRef;
+use super::ConfigResult;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::thread;
+use std::thread::Builder;
+use std::time::Duration;
+use std::time::SystemTime;
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+static RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Configuration which can be replaced while application is running. Worker and handlers take snapshot of current
+/// configuration and use it until the end of update cycle or request.
+#[derive(Debug, Clone)]
+pub struct SharedConfig {
+    path: Arc<PathBuf>,
+    inner: Arc<RwLock<ConfigRef>>,
+}
+
+impl SharedConfig {
+    pub fn new(path: &Path, config: ConfigRef) -> SharedConfig {
+        SharedConfig {
+            path: Arc::new(path.into()),
+            inner: Arc::new(RwLock::new(config)),
+        }
+    }
+
+    /// Returns current configuration.
+    pub fn get(&self) -> ConfigRef {
+        self.inner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Load configuration from file, validate it and replace current configuration. If new configuration can't be
+    /// loaded or is not valid, current configuration stays active.
+    pub fn reload(&self) -> ConfigResult<()> {
+        let config = load(self.path.as_path())?;
+
+        validate(config.clone())?;
+
+        *self.inner.write().unwrap_or_else(PoisonError::into_inner) = config;
+        info!("Configuration reloaded from {}", self.path.display());
+
+        Ok(())
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        fs::metadata(self.path.as_path())
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+extern "C" fn request_reload(_signal: libc::c_int) {
+    RELOAD_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Reload configuration when its file is modified or application receives SIGHUP.
+pub fn watch(config: SharedConfig) {
+    // SAFETY: signal handler only stores to atomic flag, which is async-signal-safe.
+    unsafe {
+        libc::signal(
+            libc::SIGHUP,
+            request_reload as *const () as libc::sighandler_t,
+        );
+    }
+
+    let result = Builder::new()
+        .name("config watcher".to_string())
+        .spawn(move || {
+            let mut modified = config.modified();
+
+            loop {
+                thread::sleep(WATCH_INTERVAL);
+
+                let current = config.modified();
+                let changed = current.is_some() && current != modified;
+
+                if RELOAD_REQUESTED.swap(false, Ordering::SeqCst) || changed {
+                    modified = current;
+
+                    if let Err(err) = config.reload() {
+                        error!(
+                            "Failed to reload configuration, previous one stays active - {}",
+                            err
+                        );
+                    }
+                }
+            }
+        });
+
+    if let Err(err) = result {
+        warn!("Failed to start config watcher - {}", err);
+    }
+}
@@ -0,0 +1,328 @@
+use super::ConfigError;
+use super::ConfigResult;
+use super::ServerConfig;
+use serde::de::Error as DeError;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde_yaml::Value;
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+const DEFAULT_HOST: &str = "localhost";
+const DEFAULT_PORT: u16 = 5432;
+const PGPASS_DATABASE: &str = "postgres";
+
+/// Replace `${NAME}` in every string of configuration with value of environment variable `NAME`. Sequence `$${` is
+/// replaced with literal `${`. If the whole string is one reference, value which is a number or boolean is used as
+/// number or boolean, so non-string parameters can be set from environment too.
+pub fn interpolate(value: &mut Value) -> ConfigResult<()> {
+    match value {
+        Value::String(string) => {
+            let whole = is_single_reference(string);
+            let interpolated = interpolate_string(string)?;
+
+            *value = if whole {
+                parse_scalar(interpolated)
+            } else {
+                Value::String(interpolated)
+            };
+        }
+        Value::Sequence(sequence) => {
+            for value in sequence {
+                interpolate(value)?;
+            }
+        }
+        Value::Mapping(mapping) => {
+            for (_, value) in mapping.iter_mut() {
+                interpolate(value)?;
+            }
+        }
+        Value::Tagged(tagged) => interpolate(&mut tagged.value)?,
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+
+    Ok(())
+}
+
+fn interpolate_string(string: &str) -> ConfigResult<String> {
+    let mut result = String::with_capacity(string.len());
+    let mut rest = string;
+
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            result.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = rest.strip_prefix("${") {
+            let end = reference.find('}').ok_or_else(|| {
+                ConfigError::format(format_args!(
+                    "unterminated environment variable reference in `{}`",
+                    string
+                ))
+            })?;
+            let name = &reference[..end];
+
+            result.push_str(&env_var(name)?);
+            rest = &reference[end + 1..];
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+fn is_single_reference(string: &str) -> bool {
+    string
+        .strip_prefix("${")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .is_some_and(|name| !name.contains('}'))
+}
+
+/// Parse value of environment variable as YAML number or boolean. Value is kept as string if it is written differently
+/// from its parsed form, so strings like `0123` are not changed.
+fn parse_scalar(text: String) -> Value {
+    match serde_yaml::from_str::<Value>(&text) {
+        Ok(Value::Number(number)) if number.to_string() == text => Value::Number(number),
+        Ok(Value::Bool(value)) if value.to_string() == text => Value::Bool(value),
+        _ => Value::String(text),
+    }
+}
+
+/// Deserialize string parameter which may be also set as number or boolean, for example password set from environment
+/// variable with digits only.
+pub fn scalar_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::String(string) => Ok(string),
+        Value::Number(number) => Ok(number.to_string()),
+        Value::Bool(value) => Ok(value.to_string()),
+        _ => Err(D::Error::custom("expected a string")),
+    }
+}
+
+fn env_var(name: &str) -> ConfigResult<String> {
+    env::var(name).map_err(|err| {
+        ConfigError::format(format_args!(
+            "environment variable {} referenced in configuration - {}",
+            name, err
+        ))
+    })
+}
+
+/// Fill connection parameters which are not set in configuration. Host, port and role default to `PGHOST`, `PGPORT`
+/// and `PGUSER`. Password is taken from `password`, `password_file` or `password_env`, otherwise from `PGPASSWORD` or
+/// password file (`PGPASSFILE` or `~/.pgpass`).
+pub fn resolve(server: &mut ServerConfig) -> ConfigResult<()> {
+    if server.host.is_empty() {
+        server.host = env::var("PGHOST").unwrap_or_else(|_| DEFAULT_HOST.into());
+    }
+
+    if server.port == 0 {
+        server.port = match env::var("PGPORT") {
+            Ok(port) => port.parse().map_err(|_| {
+                ConfigError::format(format_args!("PGPORT `{}` is not a valid port", port))
+            })?,
+            Err(_) => DEFAULT_PORT,
+        };
+    }
+
+    if server.role.is_empty() {
+        server.role = env::var("PGUSER").map_err(|_| {
+            ConfigError::format(format_args!(
+                "server {} has no role and PGUSER is not set",
+                server.name()
+            ))
+        })?;
+    }
+
+    let sources = [
+        !server.password.is_empty(),
+        server.password_file.is_some(),
+        server.password_env.is_some(),
+    ];
+
+    if sources.iter().filter(|source| **source).count() > 1 {
+        return Err(ConfigError::format(format_args!(
+            "server {} must define only one of password, password_file and password_env",
+            server.name()
+        )));
+    }
+
+    if let Some(ref path) = server.password_file {
+        let password = fs::read_to_string(path).map_err(|err| {
+            ConfigError::format(format_args!(
+                "failed to read password file {} of server {} - {}",
+                path.display(),
+                server.name(),
+                err
+            ))
+        })?;
+
+        server.password = password.trim_end_matches(['\r', '\n']).into();
+    } else if let Some(ref name) = server.password_env {
+        server.password = env::var(name).map_err(|err| {
+            ConfigError::format(format_args!(
+                "password variable {} of server {} - {}",
+                name,
+                server.name(),
+                err
+            ))
+        })?;
+    } else if server.password.is_empty() {
+        server.password = match env::var("PGPASSWORD") {
+            Ok(password) => password,
+            Err(_) => pgpass(&server.host, server.port, &server.role).unwrap_or_default(),
+        };
+    }
+
+    Ok(())
+}
+
+/// Find password in PostgreSQL password file. Like libpq, file accessible by group or others is ignored.
+fn pgpass(host: &str, port: u16, role: &str) -> Option<String> {
+    let path = match env::var_os("PGPASSFILE") {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(env::var_os("HOME")?).join(".pgpass"),
+    };
+    let metadata = fs::metadata(&path).ok()?;
+
+    if metadata.permissions().mode() & 0o077 != 0 {
+        warn!(
+            "Password file {} has group or world access, it is ignored",
+            path.display()
+        );
+
+        return None;
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|err| warn!("Failed to read password file {} - {}", path.display(), err))
+        .ok()?;
+    let port = port.to_string();
+    let expected = [host, port.as_str(), PGPASS_DATABASE, role];
+
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(split_pgpass_line)
+        .find(|fields| {
+            fields.len() == 5
+                && fields
+                    .iter()
+                    .zip(&expected)
+                    .all(|(field, expected)| field == "*" || field == expected)
+        })
+        .map(|mut fields| fields.remove(4))
+}
+
+/// Split line of password file by `:`, characters can be escaped with `\`.
+fn split_pgpass_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    fields.last_mut().unwrap().push(c);
+                }
+            }
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolate_string_replaces_references() {
+        env::set_var("SECRETS_TEST_HOST", "db.local");
+        env::set_var("SECRETS_TEST_PORT", "5433");
+
+        assert_eq!(
+            interpolate_string("${SECRETS_TEST_HOST}:${SECRETS_TEST_PORT}/x").unwrap(),
+            "db.local:5433/x"
+        );
+        assert_eq!(
+            interpolate_string("no references").unwrap(),
+            "no references"
+        );
+    }
+
+    #[test]
+    fn interpolate_string_keeps_escaped_and_single_dollars() {
+        assert_eq!(
+            interpolate_string("$${NAME} costs $5 $").unwrap(),
+            "${NAME} costs $5 $"
+        );
+    }
+
+    #[test]
+    fn interpolate_string_rejects_invalid_references() {
+        assert!(interpolate_string("${SECRETS_TEST_UNSET}").is_err());
+        assert!(interpolate_string("${SECRETS_TEST_HOST").is_err());
+    }
+
+    #[test]
+    fn interpolate_parses_single_references() {
+        env::set_var("SECRETS_TEST_NUMBER", "5432");
+        env::set_var("SECRETS_TEST_BOOL", "true");
+        env::set_var("SECRETS_TEST_ZERO", "0123");
+
+        let mut value: Value = serde_yaml::from_str(
+            "port: ${SECRETS_TEST_NUMBER}\nforce: ${SECRETS_TEST_BOOL}\n\
+             password: ${SECRETS_TEST_ZERO}\nhost: h${SECRETS_TEST_NUMBER}",
+        )
+        .unwrap();
+
+        interpolate(&mut value).unwrap();
+
+        assert_eq!(value["port"], Value::from(5432));
+        assert_eq!(value["force"], Value::Bool(true));
+        assert_eq!(value["password"], Value::from("0123"));
+        assert_eq!(value["host"], Value::from("h5432"));
+    }
+
+    #[test]
+    fn scalar_string_accepts_numbers() {
+        #[derive(Deserialize)]
+        struct Server {
+            #[serde(deserialize_with = "scalar_string")]
+            password: String,
+        }
+
+        let server: Server = serde_yaml::from_str("password: 123456").unwrap();
+
+        assert_eq!(server.password, "123456");
+        assert!(serde_yaml::from_str::<Server>("password: [1]").is_err());
+    }
+
+    #[test]
+    fn split_pgpass_line_handles_escapes() {
+        assert_eq!(
+            split_pgpass_line("host:5432:*:user:pass"),
+            vec!["host", "5432", "*", "user", "pass"]
+        );
+        assert_eq!(
+            split_pgpass_line(r"host:5432:db:user:pa\:ss\\word"),
+            vec!["host", "5432", "db", "user", r"pa:ss\word"]
+        );
+        assert_eq!(split_pgpass_line("a:b::"), vec!["a", "b", "", ""]);
+        assert_eq!(split_pgpass_line(r"trailing\"), vec!["trailing"]);
+    }
+}
@@ -1,51 +1,596 @@
+use super::AttributionRule;
+use super::Config;
 use super::ConfigError;
 use super::ConfigRef;
 use super::ConfigResult;
+use super::Cors;
 use super::DiskConfig;
+use super::RetentionRule;
+use super::SslMode;
+use crate::auth;
+use iron::url::Url;
 use std::collections::HashSet;
+use std::env;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
 
+/// Minimal number of PBKDF2 iterations of password hash which is not reported as weak.
+const MIN_HASH_ITERATIONS: u32 = 100_000;
+
+/// Validate configuration. Warnings are logged, configuration with errors is rejected with all errors in message.
 #[allow(clippy::needless_pass_by_value)]
 pub fn validate(config: ConfigRef) -> ConfigResult<()> {
-    validate_number(config.update_interval(), "update_interval")?;
+    let issues = check(&config);
+    let mut errors = Vec::new();
+
+    for issue in &issues {
+        match issue.severity() {
+            Severity::Warning => warn!("Configuration {}", issue),
+            Severity::Error => errors.push(format!("{}", issue)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigError::format(format_args!("{}", errors.join("; "))))
+    }
+}
+
+/// Returns every problem found in configuration.
+pub fn check(config: &Config) -> Vec<Issue> {
+    let mut validator = Validator::default();
+
+    validator.validate(config);
+
+    validator
+        .issues
+        .into_iter()
+        .map(|mut issue| {
+            issue.line = locate(&config.source, &issue.path);
+            issue
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
 
-    match config.server().disk() {
-        DiskConfig::Fixed {
-            capacity,
-            soft_threshold,
-            hard_threshold,
-            ..
-        } => {
-            validate_number(*capacity, "capacity")?;
-            validate_number(*soft_threshold, "soft_threshold")?;
-            validate_number(*hard_threshold, "hard_threshold")?;
+/// Problem found in configuration with YAML path of the element which caused it.
+#[derive(Debug)]
+pub struct Issue {
+    severity: Severity,
+    path: String,
+    line: Option<usize>,
+    message: String,
+}
+
+impl Issue {
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self.line {
+            Some(line) => write!(
+                f,
+                "{}: {} (line {}): {}",
+                self.severity, self.path, line, self.message
+            ),
+            None => write!(f, "{}: {}: {}", self.severity, self.path, self.message),
         }
-        DiskConfig::Command { .. } => {}
     }
+}
 
-    let mut logins = HashSet::new();
+#[derive(Debug, Default)]
+struct Validator {
+    issues: Vec<Issue>,
+}
+
+impl Validator {
+    fn error(&mut self, path: &str, message: String) {
+        self.add(Severity::Error, path, message);
+    }
+
+    fn warning(&mut self, path: &str, message: String) {
+        self.add(Severity::Warning, path, message);
+    }
+
+    fn add(&mut self, severity: Severity, path: &str, message: String) {
+        self.issues.push(Issue {
+            severity,
+            path: path.into(),
+            line: None,
+            message,
+        });
+    }
+
+    fn number(&mut self, path: &str, value: u64) {
+        if value == 0 {
+            self.error(
+                path,
+                format!("must be greater than zero, but {} given", value),
+            );
+        }
+    }
+
+    fn validate(&mut self, config: &Config) {
+        self.number("update_interval", config.update_interval());
+
+        if config.servers().is_empty() {
+            self.error("servers", "at least one server must be defined".into());
+        }
+
+        self.validate_servers(config);
+
+        if let Some(history) = config.history() {
+            self.number("history.retention", history.retention());
+
+            if let Some(downsample) = history.downsample() {
+                self.number("history.downsample.after", downsample.after());
+                self.number("history.downsample.interval", downsample.interval());
+            }
+        }
+
+        if let Some(bloat) = config.bloat() {
+            self.number("bloat.interval", bloat.interval());
+        }
+
+        if let Some(health) = config.health() {
+            self.number("health.stale_after", health.stale_after());
+        }
+
+        if let Some(retention) = config.retention() {
+            for (index, rule) in retention.rules().iter().enumerate() {
+                let path = format!("retention.rules[{}].days", index);
+
+                match rule {
+                    RetentionRule::UserDatabaseAge { days } => self.number(&path, *days),
+                    RetentionRule::PatternAge { days, .. } => self.number(&path, *days),
+                    RetentionRule::DiskHardThreshold => {}
+                }
+            }
+        }
+
+        if let Some(Cors::Whitelist { whitelist }) = config.cors() {
+            let mut origins: Vec<&String> = whitelist.iter().collect();
+            origins.sort();
+
+            for origin in origins {
+                self.validate_origin(origin);
+            }
+        }
+
+        self.validate_users(config);
+    }
+
+    fn validate_servers(&mut self, config: &Config) {
+        let mut names = HashSet::new();
+
+        for (index, server) in config.servers().iter().enumerate() {
+            let path = config.server_path(index);
+            let name = server.name();
+
+            if !names.insert(name) {
+                self.error(
+                    &format!("{}.name", path),
+                    format!("server list contains duplicate name {}", name),
+                );
+            }
+
+            self.number(
+                &format!("{}.pool.size", path),
+                u64::from(server.pool().size()),
+            );
+            self.number(
+                &format!("{}.pool.idle_timeout", path),
+                server.pool().idle_timeout(),
+            );
+            self.number(
+                &format!("{}.connect_timeout", path),
+                server.connect_timeout(),
+            );
+
+            self.number(
+                &format!("{}.statement_timeout", path),
+                server.statement_timeout(),
+            );
+
+            if server.ssl_cert().is_some() != server.ssl_key().is_some() {
+                self.error(
+                    &format!("{}.ssl_cert", path),
+                    format!("server {} must define both ssl_cert and ssl_key", name),
+                );
+            }
+
+            if server.ssl_mode() == SslMode::Disable
+                && (server.ssl_root_cert().is_some() || server.ssl_cert().is_some())
+            {
+                self.error(
+                    &format!("{}.ssl_mode", path),
+                    format!(
+                        "server {} defines TLS certificates but ssl_mode is disable",
+                        name
+                    ),
+                );
+            }
+
+            let path = format!("{}.disk", path);
+
+            match server.disk() {
+                DiskConfig::Fixed {
+                    capacity,
+                    soft_threshold,
+                    hard_threshold,
+                    ..
+                } => {
+                    self.number(&format!("{}.capacity", path), *capacity);
+                    self.number(&format!("{}.soft_threshold", path), *soft_threshold);
+                    self.number(&format!("{}.hard_threshold", path), *hard_threshold);
+
+                    if soft_threshold >= hard_threshold {
+                        self.error(
+                            &format!("{}.soft_threshold", path),
+                            format!(
+                                "must be less than hard_threshold {}, but {} given",
+                                hard_threshold, soft_threshold
+                            ),
+                        );
+                    }
+
+                    if hard_threshold > capacity {
+                        self.error(
+                            &format!("{}.hard_threshold", path),
+                            format!(
+                                "must not exceed capacity {}, but {} given",
+                                capacity, hard_threshold
+                            ),
+                        );
+                    }
+                }
+                DiskConfig::Command {
+                    command, timeout, ..
+                } => {
+                    self.number(&format!("{}.timeout", path), *timeout);
+
+                    if let Err(message) = check_executable(command) {
+                        self.error(&format!("{}.command", path), message);
+                    }
+                }
+            }
+        }
+    }
 
-    for user in config.users() {
-        let user_login = user.login();
-        let login = user_login.to_lowercase();
+    fn validate_origin(&mut self, origin: &str) {
+        let path = "cors.whitelist";
 
-        if !logins.insert(login) {
-            return Err(ConfigError::format(format_args!(
-                "user list contains duplicate login {}",
-                user_login
-            )));
+        match Url::parse(origin) {
+            Ok(url) if url.cannot_be_a_base() || url.host().is_none() => {
+                self.error(path, format!("origin {} must have scheme and host", origin));
+            }
+            Ok(url) => {
+                // Browsers send origin without path, so origin with path never matches.
+                if url.path() != "/" || origin.ends_with('/') || url.query().is_some() {
+                    self.warning(
+                        path,
+                        format!("origin {} must contain only scheme, host and port", origin),
+                    );
+                }
+            }
+            Err(err) => self.error(
+                path,
+                format!("origin {} is not valid URL - {}", origin, err),
+            ),
         }
     }
 
-    Ok(())
+    fn validate_users(&mut self, config: &Config) {
+        let mut logins = HashSet::new();
+
+        for (index, user) in config.users().iter().enumerate() {
+            let path = format!("users[{}]", index);
+            let user_login = user.login();
+            let login = user_login.to_lowercase();
+
+            if !logins.insert(login) {
+                self.error(
+                    &format!("{}.login", path),
+                    format!("user list contains duplicate login {}", user_login),
+                );
+            }
+
+            if let Some(quota) = user.quota() {
+                if let Some(size) = quota.size() {
+                    self.number(&format!("{}.quota.size", path), size);
+                }
+
+                if let Some(soft_size) = quota.soft_size() {
+                    self.number(&format!("{}.quota.soft_size", path), soft_size);
+                }
+
+                if let Some(databases) = quota.databases() {
+                    self.number(&format!("{}.quota.databases", path), databases);
+                }
+            }
+
+            if let Some(hash) = user.password_hash() {
+                let hash_path = format!("{}.password_hash", path);
+
+                if !auth::is_valid_hash(hash) {
+                    self.error(
+                        &hash_path,
+                        format!(
+                            "password hash of user {} must have format pbkdf2-sha256:ITERATIONS:SALT:HEX_DIGEST",
+                            user_login
+                        ),
+                    );
+                } else if auth::hash_iterations(hash)
+                    .is_some_and(|iterations| iterations < MIN_HASH_ITERATIONS)
+                {
+                    self.warning(
+                        &hash_path,
+                        format!(
+                            "password hash of user {} uses less than {} iterations",
+                            user_login, MIN_HASH_ITERATIONS
+                        ),
+                    );
+                }
+            }
+        }
+
+        let substring_rules = config.attribution_rules().iter().any(|rule| {
+            matches!(
+                rule,
+                AttributionRule::Prefix | AttributionRule::Suffix | AttributionRule::Contains
+            )
+        });
+
+        if substring_rules {
+            let users = config.users();
+
+            for (index, user) in users.iter().enumerate() {
+                let login = user.login().to_lowercase();
+
+                for other in users {
+                    let other_login = other.login().to_lowercase();
+
+                    if other_login != login && other_login.contains(&login) {
+                        self.warning(
+                            &format!("users[{}].login", index),
+                            format!(
+                                "login {} is part of login {}, databases of {} may be attributed to {}",
+                                user.login(),
+                                other.login(),
+                                other.login(),
+                                user.login()
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        for (index, rule) in config.attribution_rules().iter().enumerate() {
+            if let AttributionRule::Override { databases } = rule {
+                let mut databases: Vec<(&String, &String)> = databases.iter().collect();
+                databases.sort();
+
+                for (database, login) in databases {
+                    if !logins.contains(&login.to_lowercase()) {
+                        self.error(
+                            &format!("attribution.rules[{}].databases", index),
+                            format!(
+                                "attribution override of database {} refers to unknown user {}",
+                                database, login
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    }
 }
 
-fn validate_number(value: u64, name: &str) -> ConfigResult<()> {
-    if value > 0 {
+/// Check that command exists and can be executed. Command without `/` is searched in `PATH`.
+fn check_executable(command: &str) -> Result<(), String> {
+    let is_executable = |path: &Path| {
+        fs::metadata(path)
+            .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+    };
+
+    if command.contains('/') {
+        return match fs::metadata(command) {
+            Ok(_) if is_executable(Path::new(command)) => Ok(()),
+            Ok(_) => Err(format!("command {} is not executable", command)),
+            Err(err) => Err(format!("command {} - {}", command, err)),
+        };
+    }
+
+    let found = env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| is_executable(&dir.join(command))));
+
+    if found {
         Ok(())
     } else {
-        Err(ConfigError::format(format_args!(
-            "{} must be greater than zero, but {} given",
-            name, value
-        )))
+        Err(format!("command {} is not found in PATH", command))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Split path like `servers[0].disk.capacity` into keys and indexes.
+fn segments(path: &str) -> Vec<Segment<'_>> {
+    let mut result = Vec::new();
+
+    for part in path.split('.') {
+        let mut pieces = part.split('[');
+
+        if let Some(key) = pieces.next() {
+            result.push(Segment::Key(key));
+        }
+
+        for index in pieces {
+            match index.trim_end_matches(']').parse() {
+                Ok(index) => result.push(Segment::Index(index)),
+                Err(_) => return result,
+            }
+        }
+    }
+
+    result
+}
+
+/// Find line of YAML element by its path. Only block collections are followed, for elements inside flow collections
+/// line of the closest found parent is returned.
+fn locate(source: &str, path: &str) -> Option<usize> {
+    // Every line is split into sequence item markers and content, each with its column.
+    let mut lines: Vec<(usize, usize, &str)> = Vec::new();
+
+    for (number, line) in source.lines().enumerate() {
+        let content = line.trim_start();
+
+        if content.is_empty() || content.starts_with('#') || content.starts_with("---") {
+            continue;
+        }
+
+        let mut column = line.len() - content.len();
+        let mut content = content;
+
+        while content == "-" || content.starts_with("- ") {
+            lines.push((number + 1, column, "-"));
+
+            let rest = content[1..].trim_start();
+            column += content.len() - rest.len();
+            content = rest;
+        }
+
+        if !content.is_empty() {
+            lines.push((number + 1, column, content));
+        }
+    }
+
+    let mut start = 0;
+    let mut end = lines.len();
+    let mut found = None;
+
+    for segment in segments(path) {
+        if start >= end {
+            break;
+        }
+
+        let column = lines[start].1;
+        let mut siblings = (start..end).filter(|&index| lines[index].1 == column);
+        let position = match segment {
+            Segment::Key(key) => siblings.find(|&index| is_key(lines[index].2, key)),
+            Segment::Index(item) => siblings.filter(|&index| lines[index].2 == "-").nth(item),
+        };
+
+        match position {
+            Some(index) => {
+                found = Some(lines[index].0);
+                start = index + 1;
+                end = (start..end)
+                    .find(|&next| lines[next].1 <= column)
+                    .unwrap_or(end);
+            }
+            None => break,
+        }
+    }
+
+    found
+}
+
+fn is_key(content: &str, key: &str) -> bool {
+    [
+        key.to_string(),
+        format!("\"{}\"", key),
+        format!("'{}'", key),
+    ]
+    .iter()
+    .any(|quoted| {
+        content
+            .strip_prefix(quoted.as_str())
+            .and_then(|rest| rest.strip_prefix(':'))
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"---
+update_interval: 60
+servers:
+  # primary server
+  - name: "a"
+    disk:
+      capacity: 10
+  - name: b
+    disk: { capacity: 5 }
+    "port": 5432
+users: []
+"#;
+
+    #[test]
+    fn segments_splits_keys_and_indexes() {
+        assert_eq!(
+            segments("servers[0].disk.capacity"),
+            vec![
+                Segment::Key("servers"),
+                Segment::Index(0),
+                Segment::Key("disk"),
+                Segment::Key("capacity"),
+            ]
+        );
+        assert_eq!(
+            segments("a[1][2]"),
+            vec![Segment::Key("a"), Segment::Index(1), Segment::Index(2)]
+        );
+    }
+
+    #[test]
+    fn segments_stops_at_invalid_index() {
+        assert_eq!(segments("a[x].b"), vec![Segment::Key("a")]);
+    }
+
+    #[test]
+    fn locate_finds_nested_elements() {
+        assert_eq!(locate(SOURCE, "update_interval"), Some(2));
+        assert_eq!(locate(SOURCE, "servers[0]"), Some(5));
+        assert_eq!(locate(SOURCE, "servers[0].disk.capacity"), Some(7));
+        assert_eq!(locate(SOURCE, "servers[1].name"), Some(8));
+        assert_eq!(locate(SOURCE, "servers[1].port"), Some(10));
+        assert_eq!(locate(SOURCE, "users"), Some(11));
+    }
+
+    #[test]
+    fn locate_returns_closest_parent() {
+        assert_eq!(locate(SOURCE, "servers[1].disk.capacity"), Some(9));
+        assert_eq!(locate(SOURCE, "servers[0].disk.offset"), Some(6));
+        assert_eq!(locate(SOURCE, "servers[5]"), Some(3));
+        assert_eq!(locate(SOURCE, "missing"), None);
     }
 }
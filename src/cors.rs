@@ -0,0 +1,89 @@
+use crate::config::ConfigRef;
+use crate::config::Cors;
+use crate::config::SharedConfig;
+use iron::middleware::AroundMiddleware;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request;
+use iron::Response;
+use iron_cors::CorsMiddleware as IronCorsMiddleware;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+type HandlerRef = Arc<dyn Handler>;
+
+/// CORS middleware which follows `cors` section of current configuration.
+#[derive(Debug)]
+pub struct CorsMiddleware {
+    config: SharedConfig,
+}
+
+impl CorsMiddleware {
+    pub fn new(config: SharedConfig) -> CorsMiddleware {
+        CorsMiddleware { config }
+    }
+}
+
+impl AroundMiddleware for CorsMiddleware {
+    fn around(self, handler: Box<dyn Handler>) -> Box<dyn Handler> {
+        Box::new(CorsHandler {
+            config: self.config,
+            handler: Arc::from(handler),
+            current: Mutex::new(None),
+        })
+    }
+}
+
+/// Wraps handler into CORS handler of `iron_cors`. Wrapped handler is rebuilt when configuration is reloaded.
+struct CorsHandler {
+    config: SharedConfig,
+    handler: HandlerRef,
+    current: Mutex<Option<(ConfigRef, HandlerRef)>>,
+}
+
+impl CorsHandler {
+    fn wrapped(&self) -> HandlerRef {
+        let config = self.config.get();
+        let mut current = self.current.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match *current {
+            Some((ref built_for, ref handler)) if Arc::ptr_eq(built_for, &config) => {
+                handler.clone()
+            }
+            _ => {
+                let handler = SharedHandler(self.handler.clone());
+                let wrapped: HandlerRef = Arc::from(match config.cors() {
+                    Some(Cors::AllowAny) => {
+                        IronCorsMiddleware::with_allow_any().around(Box::new(handler))
+                    }
+                    Some(Cors::Whitelist { whitelist }) => {
+                        IronCorsMiddleware::with_whitelist(whitelist.clone())
+                            .around(Box::new(handler))
+                    }
+                    None => Box::new(handler),
+                });
+                *current = Some((config, wrapped.clone()));
+
+                wrapped
+            }
+        }
+    }
+}
+
+impl Handler for CorsHandler {
+    fn handle(&self, request: &mut Request) -> IronResult<Response> {
+        // Lock is released before request is handled, so requests are not serialized.
+        let handler = self.wrapped();
+
+        handler.handle(request)
+    }
+}
+
+struct SharedHandler(HandlerRef);
+
+impl Handler for SharedHandler {
+    fn handle(&self, request: &mut Request) -> IronResult<Response> {
+        self.0.handle(request)
+    }
+}
@@ -1,4 +1,7 @@
+use crate::audit::AuditError;
 use crate::config::ConfigError;
+use crate::history::HistoryError;
+use crate::tls::TlsError;
 use std::error::Error;
 use std::fmt::Display;
 use std::fmt::Formatter;
@@ -7,9 +10,14 @@ use std::fmt::Result as FmtResult;
 pub type ApplicationResult = Result<(), ApplicationError>;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum ApplicationError {
     LoadConfigError { message: String },
     ConfigError { message: String },
+    HistoryError { message: String },
+    AuditError { message: String },
+    TlsError { message: String },
+    CommandError { message: String },
 }
 
 impl ApplicationError {
@@ -30,6 +38,47 @@ impl ApplicationError {
             message: format!("{}", error),
         }
     }
+
+    pub fn invalid_config(errors: usize) -> ApplicationError {
+        ApplicationError::ConfigError {
+            message: format!("configuration has {} errors", errors),
+        }
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn history_error(error: HistoryError) -> ApplicationError {
+        error!("Failed to open history - {}", error);
+
+        ApplicationError::HistoryError {
+            message: format!("{}", error),
+        }
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn audit_error(error: AuditError) -> ApplicationError {
+        error!("Failed to open audit log - {}", error);
+
+        ApplicationError::AuditError {
+            message: format!("{}", error),
+        }
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn tls_error(error: TlsError) -> ApplicationError {
+        error!("Failed to load TLS certificate - {}", error);
+
+        ApplicationError::TlsError {
+            message: format!("{}", error),
+        }
+    }
+
+    pub fn command_error(message: &str) -> ApplicationError {
+        error!("{}", message);
+
+        ApplicationError::CommandError {
+            message: message.into(),
+        }
+    }
 }
 
 impl Error for ApplicationError {}
@@ -39,6 +88,10 @@ impl Display for ApplicationError {
         match self {
             ApplicationError::LoadConfigError { message } => write!(f, "{}", message),
             ApplicationError::ConfigError { message } => write!(f, "{}", message),
+            ApplicationError::HistoryError { message } => write!(f, "{}", message),
+            ApplicationError::AuditError { message } => write!(f, "{}", message),
+            ApplicationError::TlsError { message } => write!(f, "{}", message),
+            ApplicationError::CommandError { message } => write!(f, "{}", message),
         }
     }
 }
@@ -0,0 +1,69 @@
+use super::util::handle_empty;
+use super::util::query_param;
+use super::util::query_params;
+use super::HandlerError;
+use crate::audit::AuditRef;
+use crate::audit::Record;
+use crate::auth;
+use crate::auth::AuthUser;
+use crate::config::SharedConfig;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+
+const DEFAULT_LIMIT: usize = 50;
+
+#[derive(Debug)]
+pub struct AuditHandler {
+    config: SharedConfig,
+    audit: AuditRef,
+}
+
+impl AuditHandler {
+    pub fn new(config: SharedConfig, audit: AuditRef) -> AuditHandler {
+        AuditHandler { config, audit }
+    }
+}
+
+impl Handler for AuditHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        let user = request.extensions.get::<AuthUser>().cloned();
+
+        // Audit log contains addresses of all users, so only administrators can read it.
+        if !auth::is_privileged(&config, user.as_ref()) {
+            return Ok(auth::unauthorized(&config));
+        }
+
+        let params = query_params(request);
+
+        handle_empty(move || {
+            let offset = query_param(&params, "offset")?.unwrap_or(0);
+            let limit = query_param(&params, "limit")?.unwrap_or(DEFAULT_LIMIT);
+            let (total, records) = self
+                .audit
+                .page(offset, limit)
+                .map_err(|err| HandlerError::new(&format!("Audit error - {}", err)))?;
+
+            Ok(Response::new(total, offset, records))
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    total: usize,
+    offset: usize,
+    records: Vec<Record>,
+}
+
+impl Response {
+    fn new(total: usize, offset: usize, records: Vec<Record>) -> Response {
+        Response {
+            total,
+            offset,
+            records,
+        }
+    }
+}
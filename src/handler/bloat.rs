@@ -0,0 +1,94 @@
+use super::util::handle_empty;
+use super::util::query_params;
+use super::util::route_param;
+use super::util::select_server;
+use super::util::unknown_database;
+use super::HandlerError;
+use crate::clock;
+use crate::config::BloatConfig;
+use crate::config::SharedConfig;
+use crate::postgres::Bloat;
+use crate::postgres::PostgresRef;
+use crate::state::StateRef;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+
+#[derive(Debug)]
+pub struct BloatHandler {
+    config: SharedConfig,
+    state: StateRef,
+    postgres: PostgresRef,
+}
+
+impl BloatHandler {
+    pub fn new(config: SharedConfig, state: StateRef, postgres: PostgresRef) -> BloatHandler {
+        BloatHandler {
+            config,
+            state,
+            postgres,
+        }
+    }
+}
+
+impl Handler for BloatHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        let params = query_params(request);
+        let name = route_param(request, "name");
+
+        if let Some(response) = unknown_database(&config, &self.state, &params, name.as_deref()) {
+            return Ok(response);
+        }
+
+        handle_empty(move || {
+            let name = name.ok_or_else(|| HandlerError::new("Database name is required"))?;
+            let server_config = select_server(&config, params.get("server").map(String::as_str))?;
+            let bloat_config = config.bloat();
+            let max_age = bloat_config
+                .map(BloatConfig::interval)
+                .unwrap_or_else(|| config.update_interval());
+            let now = clock::unix_timestamp();
+            let cached = self
+                .state
+                .bloat(server_config.name(), &name, now, max_age)
+                .map_err(|_| HandlerError::new("State error"))?;
+
+            if let Some(bloat) = cached {
+                return Ok(Response::new(bloat));
+            }
+
+            let postgres = self.postgres.server(server_config);
+            let bloat = postgres
+                .bloat(&name, bloat_config.is_some_and(BloatConfig::pgstattuple))
+                .map_err(|err| {
+                    HandlerError::new(&format!(
+                        "Failed to estimate bloat of database `{}` - {}",
+                        name, err
+                    ))
+                })?;
+
+            self.state
+                .set_bloat(server_config.name(), &name, bloat.clone(), now)
+                .map_err(|_| HandlerError::new("State error"))?;
+
+            Ok(Response::new(bloat))
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    reclaimable: u64,
+    relations: Vec<Bloat>,
+}
+
+impl Response {
+    fn new(relations: Vec<Bloat>) -> Response {
+        Response {
+            reclaimable: relations.iter().map(Bloat::wasted).sum(),
+            relations,
+        }
+    }
+}
@@ -1,43 +1,224 @@
-use super::util::handle_request;
+use super::util::handle_empty;
+use super::util::select_server;
 use super::HandlerError;
-use crate::config::ConfigRef;
-use crate::postgres::PostgreSQL;
+use super::HandlerResult;
+use crate::audit::AuditRef;
+use crate::audit::Record;
+use crate::auth;
+use crate::auth::AuthUser;
+use crate::clock;
+use crate::config::ServerConfig;
+use crate::config::SharedConfig;
+use crate::postgres::DatabaseError;
+use crate::postgres::PostgresRef;
+use crate::postgres::Session;
+use crate::state::StateRef;
 use iron::middleware::Handler;
+use iron::status;
 use iron::IronResult;
 use iron::Request as IronRequest;
 use iron::Response as IronResponse;
+use std::io::Read;
 
 #[derive(Debug)]
 pub struct DropDbHandler {
-    config: ConfigRef,
+    config: SharedConfig,
+    state: StateRef,
+    postgres: PostgresRef,
+    audit: Option<AuditRef>,
 }
 
 impl DropDbHandler {
-    pub fn new(config: ConfigRef) -> DropDbHandler {
-        DropDbHandler { config }
+    pub fn new(
+        config: SharedConfig,
+        state: StateRef,
+        postgres: PostgresRef,
+        audit: Option<AuditRef>,
+    ) -> DropDbHandler {
+        DropDbHandler {
+            config,
+            state,
+            postgres,
+            audit,
+        }
+    }
+
+    fn drop_database(
+        &self,
+        user: Option<&AuthUser>,
+        server_config: &ServerConfig,
+        request: &Request,
+    ) -> HandlerResult<()> {
+        let name = &request.name;
+
+        if let Some(user) = user {
+            self.authorize(user, server_config, name)?;
+        }
+
+        // Users are known only when authentication is configured, client addresses are hidden from non-administrators.
+        let privileged = user.is_none_or(AuthUser::is_admin);
+        let postgres = self.postgres.server(server_config);
+
+        postgres
+            .drop_database(
+                name,
+                server_config.service_databases(),
+                server_config.drop_requires_force(),
+                request.force,
+            )
+            .map_err(|err| {
+                let message = format!("Failed to drop database `{}` - {}", name, err);
+
+                match err {
+                    DatabaseError::ActiveSessions { sessions } => {
+                        let sessions: Vec<Session> = if privileged {
+                            sessions
+                        } else {
+                            sessions
+                                .into_iter()
+                                .map(Session::without_client_address)
+                                .collect()
+                        };
+
+                        HandlerError::with_details(
+                            &message,
+                            &ActiveSessions {
+                                sessions: &sessions,
+                            },
+                        )
+                    }
+                    _ => HandlerError::new(&message),
+                }
+            })
+    }
+
+    fn audit(&self, record: Record) {
+        if let Some(ref audit) = self.audit {
+            if let Err(err) = audit.append(&record) {
+                error!("Failed to write audit record - {}", err);
+            }
+        }
+    }
+
+    /// Check that user allowed to drop database. Administrators can drop any database, other users can drop only
+    /// databases attributed to them.
+    fn authorize(
+        &self,
+        user: &AuthUser,
+        server_config: &ServerConfig,
+        name: &str,
+    ) -> HandlerResult<()> {
+        if user.is_admin() {
+            return Ok(());
+        }
+
+        let database = self
+            .state
+            .database(server_config.name(), name)
+            .map_err(|_| HandlerError::new("State error"))?;
+
+        match database.as_ref().and_then(|database| database.user()) {
+            Some(owner) if owner == user.login() => Ok(()),
+            _ => Err(HandlerError::new(&format!(
+                "User `{}` is not allowed to drop database `{}`",
+                user.login(),
+                name
+            ))),
+        }
     }
 }
 
 impl Handler for DropDbHandler {
     fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
-        handle_request(request, move |request: Request| {
-            let name = request.name;
-            let server_config = self.config.server();
-            let postgres = PostgreSQL::new(
-                server_config.host(),
-                server_config.port(),
-                server_config.role(),
-                server_config.password(),
+        let config = self.config.get();
+        let user = request.extensions.get::<AuthUser>().cloned();
+        let remote_address = request.remote_addr.ip().to_string();
+        let mut body = String::new();
+        let parsed = match request.body.read_to_string(&mut body) {
+            Ok(_) => serde_json::from_str::<Request>(&body).map_err(|err| format!("{}", err)),
+            Err(err) => Err(format!("{}", err)),
+        };
+
+        // Every attempt is audited, even if it is rejected before request is processed.
+        if config.authentication().is_some() && user.is_none() {
+            if let Some(ref audit) = self.audit {
+                audit.append_rejected(&remote_address, None, &body, "Authentication required");
+            }
+
+            return Ok(auth::unauthorized(&config));
+        }
+
+        let request = match parsed {
+            Ok(request) => request,
+            Err(message) => {
+                if let Some(ref audit) = self.audit {
+                    audit.append_rejected(
+                        &remote_address,
+                        user.as_ref().map(AuthUser::login),
+                        &body,
+                        &format!("Invalid request - {}", message),
+                    );
+                }
+
+                return Ok(IronResponse::with((status::BadRequest, message)));
+            }
+        };
+
+        handle_empty(move || {
+            let resolved =
+                select_server(&config, request.server.as_deref()).and_then(|server_config| {
+                    let database = self
+                        .state
+                        .database(server_config.name(), &request.name)
+                        .map_err(|_| HandlerError::new("State error"))?;
+
+                    Ok((server_config, database))
+                });
+            let (server_config, database) = match resolved {
+                Ok(resolved) => resolved,
+                Err(err) => {
+                    self.audit(
+                        Record::new(
+                            clock::unix_timestamp(),
+                            &remote_address,
+                            user.as_ref().map(AuthUser::login),
+                            request.server.as_deref().unwrap_or_default(),
+                            &request.name,
+                        )
+                        .with_outcome(Some(format!("{}", err))),
+                    );
+
+                    return Err(err);
+                }
+            };
+            let result = self.drop_database(user.as_ref(), server_config, &request);
+
+            self.audit(
+                Record::new(
+                    clock::unix_timestamp(),
+                    &remote_address,
+                    user.as_ref().map(AuthUser::login),
+                    server_config.name(),
+                    &request.name,
+                )
+                .with_database(database.as_ref())
+                .with_outcome(result.as_ref().err().map(|err| format!("{}", err))),
             );
 
-            postgres.drop_database(&name).map_err(|err| {
-                HandlerError::new(&format!("Failed to drop database `{}` - {}", name, err))
-            })
+            result
         })
     }
 }
 
 #[derive(Debug, Deserialize)]
 struct Request {
+    server: Option<String>,
     name: String,
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ActiveSessions<'a> {
+    sessions: &'a [Session],
 }
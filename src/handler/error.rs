@@ -1,3 +1,5 @@
+use serde::Serialize;
+use serde_json::Value;
 use std::error::Error;
 use std::fmt::Display;
 use std::fmt::Formatter;
@@ -8,14 +10,31 @@ pub type HandlerResult<T> = Result<T, HandlerError>;
 #[derive(Debug)]
 pub struct HandlerError {
     message: String,
+    details: Option<Value>,
 }
 
 impl HandlerError {
     pub fn new(message: &str) -> HandlerError {
         HandlerError {
             message: message.into(),
+            details: None,
         }
     }
+
+    /// Create error with additional structured information which will be sent to client with error message.
+    pub fn with_details<T>(message: &str, details: &T) -> HandlerError
+    where
+        T: Serialize,
+    {
+        HandlerError {
+            message: message.into(),
+            details: serde_json::to_value(details).ok(),
+        }
+    }
+
+    pub fn details(&self) -> Option<&Value> {
+        self.details.as_ref()
+    }
 }
 
 impl Error for HandlerError {}
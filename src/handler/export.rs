@@ -0,0 +1,175 @@
+use super::state::server_state;
+use super::util::find_server;
+use super::util::query_params;
+use super::HandlerResult;
+use crate::clock;
+use crate::config::ConfigRef;
+use crate::config::ServerConfig;
+use crate::config::SharedConfig;
+use crate::state::StateRef;
+use crate::table::human_size;
+use crate::table::Table;
+use iron::headers::Accept;
+use iron::middleware::Handler;
+use iron::mime::Attr;
+use iron::mime::Mime;
+use iron::mime::SubLevel;
+use iron::mime::TopLevel;
+use iron::mime::Value;
+use iron::status;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+use std::cmp::Reverse;
+
+/// Handler which exports databases of one or all servers as CSV or TSV, so they can be opened in spreadsheet.
+#[derive(Debug)]
+pub struct ExportHandler {
+    config: SharedConfig,
+    state: StateRef,
+}
+
+impl ExportHandler {
+    pub fn new(config: SharedConfig, state: StateRef) -> ExportHandler {
+        ExportHandler { config, state }
+    }
+
+    fn export(&self, config: &ConfigRef, server: Option<&str>) -> HandlerResult<Table> {
+        let servers: Vec<&ServerConfig> = match server {
+            Some(server) => vec![find_server(config, server)?],
+            None => config.servers().iter().collect(),
+        };
+        let mut table = Table::new(vec![
+            "server",
+            "name",
+            "user",
+            "owner",
+            "created",
+            "created_timestamp",
+            "size",
+            "size_bytes",
+            "service",
+        ]);
+
+        for server_config in servers {
+            let data = server_state(config, &self.state, server_config)?;
+
+            for database in data.databases() {
+                table.add_row(vec![
+                    server_config.name().into(),
+                    database.name().into(),
+                    database.user().unwrap_or_default().into(),
+                    database.owner().into(),
+                    clock::format_timestamp(database.modified()),
+                    database.modified().to_string(),
+                    human_size(database.size()),
+                    database.size().to_string(),
+                    database.service().to_string(),
+                ]);
+            }
+        }
+
+        Ok(table)
+    }
+}
+
+impl Handler for ExportHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        let params = query_params(request);
+        let format = match params.get("format") {
+            Some(format) => match Format::from_name(format) {
+                Some(format) => format,
+                None => {
+                    return Ok(IronResponse::with((
+                        status::BadRequest,
+                        format!("Unknown format `{}`, expected `csv` or `tsv`", format),
+                    )))
+                }
+            },
+            None => Format::from_accept(request.headers.get::<Accept>()),
+        };
+        let table = match self.export(&config, params.get("server").map(String::as_str)) {
+            Ok(table) => table,
+            Err(err) => return Ok(IronResponse::with((status::BadRequest, format!("{}", err)))),
+        };
+        let mut body = Vec::new();
+        let result = match format {
+            Format::Csv => table.write_csv(&mut body),
+            Format::Tsv => table.write_tsv(&mut body),
+        };
+
+        if let Err(err) = result {
+            return Ok(IronResponse::with((
+                status::InternalServerError,
+                format!("{}", err),
+            )));
+        }
+
+        let mut response = IronResponse::with((status::Ok, format.content_type(), body));
+        response.headers.set_raw(
+            "Content-Disposition",
+            vec![format!("attachment; filename=\"databases.{}\"", format.extension()).into_bytes()],
+        );
+
+        Ok(response)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Tsv,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "csv" => Some(Format::Csv),
+            "tsv" => Some(Format::Tsv),
+            _ => None,
+        }
+    }
+
+    /// Select format preferred by client. CSV is used if client accepts neither CSV nor TSV.
+    fn from_accept(accept: Option<&Accept>) -> Format {
+        let mut items: Vec<_> = accept
+            .map(|accept| accept.iter().collect())
+            .unwrap_or_default();
+
+        items.sort_by_key(|item| Reverse(item.quality));
+        items
+            .iter()
+            .find_map(|item| match item.item {
+                Mime(TopLevel::Text, SubLevel::Ext(ref sub_level), _) => match sub_level.as_str() {
+                    "csv" => Some(Format::Csv),
+                    "tab-separated-values" => Some(Format::Tsv),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .unwrap_or(Format::Csv)
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Tsv => "tsv",
+        }
+    }
+
+    fn content_type(self) -> Mime {
+        let mut attrs = vec![(Attr::Charset, Value::Utf8)];
+
+        if self == Format::Csv {
+            attrs.push((Attr::Ext("header".into()), Value::Ext("present".into())));
+        }
+
+        let sub_level = match self {
+            Format::Csv => "csv",
+            Format::Tsv => "tab-separated-values",
+        };
+
+        Mime(TopLevel::Text, SubLevel::Ext(sub_level.into()), attrs)
+    }
+}
@@ -0,0 +1,79 @@
+use super::util::handle_empty;
+use super::HandlerError;
+use crate::clock;
+use crate::config::SharedConfig;
+use crate::state::StateRef;
+use crate::state::WorkerStatus;
+use iron::middleware::Handler;
+use iron::status;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+
+#[derive(Debug)]
+pub struct HealthHandler {
+    config: SharedConfig,
+    state: StateRef,
+}
+
+impl HealthHandler {
+    pub fn new(config: SharedConfig, state: StateRef) -> HealthHandler {
+        HealthHandler { config, state }
+    }
+}
+
+impl Handler for HealthHandler {
+    fn handle(&self, _request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        let now = clock::unix_timestamp();
+        let stale_after = config.stale_after();
+        let mut healthy = true;
+
+        let mut response = handle_empty(|| {
+            let mut servers = Vec::new();
+
+            for server_config in config.servers() {
+                let server_name = server_config.name();
+                let worker = self
+                    .state
+                    .worker_status(server_name)
+                    .map_err(|_| HandlerError::new("State error"))?;
+                let stale = self
+                    .state
+                    .is_stale(server_name, now, stale_after)
+                    .map_err(|_| HandlerError::new("State error"))?;
+
+                healthy &= !stale;
+                servers.push(ServerHealth {
+                    name: server_name.into(),
+                    healthy: !stale,
+                    updated: worker.data_updated(),
+                    worker,
+                });
+            }
+
+            Ok(Response { healthy, servers })
+        })?;
+
+        // Load balancers and monitoring probes look only at status code, so stale data must not look like success.
+        if !healthy {
+            response.status = Some(status::ServiceUnavailable);
+        }
+
+        Ok(response)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    healthy: bool,
+    servers: Vec<ServerHealth>,
+}
+
+#[derive(Debug, Serialize)]
+struct ServerHealth {
+    name: String,
+    healthy: bool,
+    updated: Option<i64>,
+    worker: WorkerStatus,
+}
@@ -0,0 +1,106 @@
+use super::util::handle_empty;
+use super::util::query_param;
+use super::util::query_params;
+use super::util::select_server;
+use super::HandlerError;
+use crate::config::SharedConfig;
+use crate::history::HistoryRef;
+use crate::history::Sample;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+
+#[derive(Debug)]
+pub struct HistoryHandler {
+    config: SharedConfig,
+    history: HistoryRef,
+}
+
+impl HistoryHandler {
+    pub fn new(config: SharedConfig, history: HistoryRef) -> HistoryHandler {
+        HistoryHandler { config, history }
+    }
+}
+
+impl Handler for HistoryHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        let params = query_params(request);
+
+        handle_empty(move || {
+            let server_config = select_server(&config, params.get("server").map(String::as_str))?;
+            let database = params.get("database").map(String::as_str);
+            let from = query_param(&params, "from")?.unwrap_or(i64::MIN);
+            let to = query_param(&params, "to")?.unwrap_or(i64::MAX);
+            let mut samples = Vec::new();
+
+            self.history
+                .for_each(server_config.name(), database, from, to, |sample| {
+                    samples.push(SampleData::from(sample))
+                })
+                .map_err(|_| HandlerError::new("History error"))?;
+
+            Ok(Response::new(server_config.name(), database, samples))
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    server: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    database: Option<String>,
+    samples: Vec<SampleData>,
+}
+
+impl Response {
+    fn new(server: &str, database: Option<&str>, samples: Vec<SampleData>) -> Response {
+        Response {
+            server: server.into(),
+            database: database.map(String::from),
+            samples,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum SampleData {
+    Database {
+        time: i64,
+        size: u64,
+    },
+    Disk {
+        time: i64,
+        used: u64,
+        capacity: u64,
+        soft_threshold: u64,
+        hard_threshold: u64,
+        databases: usize,
+    },
+}
+
+impl From<&Sample> for SampleData {
+    fn from(sample: &Sample) -> SampleData {
+        match *sample {
+            Sample::Database { time, size, .. } => SampleData::Database { time, size },
+            Sample::Disk {
+                time,
+                used,
+                capacity,
+                soft_threshold,
+                hard_threshold,
+                databases,
+                ..
+            } => SampleData::Disk {
+                time,
+                used,
+                capacity,
+                soft_threshold,
+                hard_threshold,
+                databases,
+            },
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use iron::middleware::Handler;
+use iron::modifiers::Redirect;
+use iron::status;
+use iron::url::Url as GenericUrl;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+use iron::Url;
+
+/// Redirects every request to the same URL using HTTPS on the given port.
+#[derive(Debug)]
+pub struct HttpsRedirectHandler {
+    port: u16,
+}
+
+impl HttpsRedirectHandler {
+    pub fn new(port: u16) -> HttpsRedirectHandler {
+        HttpsRedirectHandler { port }
+    }
+}
+
+impl Handler for HttpsRedirectHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let mut url: GenericUrl = request.url.clone().into();
+
+        if url.set_scheme("https").is_err() || url.set_port(Some(self.port)).is_err() {
+            return Ok(IronResponse::with(status::BadRequest));
+        }
+
+        match Url::from_generic_url(url) {
+            Ok(url) => Ok(IronResponse::with((
+                status::MovedPermanently,
+                Redirect(url),
+            ))),
+            Err(_) => Ok(IronResponse::with(status::BadRequest)),
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use crate::config::ServerConfig;
+use crate::config::SharedConfig;
+use crate::state::StateRef;
+use crate::state::StateResult;
+use crate::state::WorkerStage;
+use iron::middleware::Handler;
+use iron::mime::Attr;
+use iron::mime::Mime;
+use iron::mime::SubLevel;
+use iron::mime::TopLevel;
+use iron::mime::Value;
+use iron::status;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+use std::fmt::Display;
+use std::fmt::Write;
+
+#[derive(Debug)]
+pub struct MetricsHandler {
+    config: SharedConfig,
+    state: StateRef,
+}
+
+impl MetricsHandler {
+    pub fn new(config: SharedConfig, state: StateRef) -> MetricsHandler {
+        MetricsHandler { config, state }
+    }
+
+    fn render(&self) -> StateResult<String> {
+        let config = self.config.get();
+        let servers = config.servers();
+        let mut metrics = Metrics::default();
+
+        metrics.family(
+            "pg_status_database_size_bytes",
+            "Database size in bytes.",
+            "gauge",
+        );
+
+        for server_config in servers {
+            let service_databases = server_config.service_databases();
+
+            self.state.for_each(server_config.name(), |database| {
+                let service = service_databases.contains(database.name());
+
+                metrics.sample(
+                    "pg_status_database_size_bytes",
+                    &[
+                        ("server", server_config.name()),
+                        ("database", database.name()),
+                        ("user", database.user().map_or("", String::as_str)),
+                        ("service", if service { "true" } else { "false" }),
+                    ],
+                    database.size(),
+                );
+            })?;
+        }
+
+        metrics.family(
+            "pg_status_database_created_timestamp",
+            "Database creation time in seconds since UNIX epoch.",
+            "gauge",
+        );
+
+        for server_config in servers {
+            self.state.for_each(server_config.name(), |database| {
+                metrics.sample(
+                    "pg_status_database_created_timestamp",
+                    &[
+                        ("server", server_config.name()),
+                        ("database", database.name()),
+                    ],
+                    database.modified(),
+                );
+            })?;
+        }
+
+        self.render_disk(
+            &mut metrics,
+            servers,
+            "pg_status_disk_used_bytes",
+            "Used disk space in bytes.",
+            |server_config| self.state.disk_used(server_config),
+        )?;
+        self.render_disk(
+            &mut metrics,
+            servers,
+            "pg_status_disk_capacity_bytes",
+            "Disk capacity in bytes.",
+            |server_config| Ok(self.state.disk_state(server_config.name())?.capacity()),
+        )?;
+        self.render_disk(
+            &mut metrics,
+            servers,
+            "pg_status_disk_soft_threshold_bytes",
+            "Soft disk usage threshold in bytes.",
+            |server_config| {
+                Ok(self
+                    .state
+                    .disk_state(server_config.name())?
+                    .soft_threshold())
+            },
+        )?;
+        self.render_disk(
+            &mut metrics,
+            servers,
+            "pg_status_disk_hard_threshold_bytes",
+            "Hard disk usage threshold in bytes.",
+            |server_config| {
+                Ok(self
+                    .state
+                    .disk_state(server_config.name())?
+                    .hard_threshold())
+            },
+        )?;
+
+        metrics.family(
+            "pg_status_disk_mount_capacity_bytes",
+            "Capacity of mount point reported by disk command in bytes.",
+            "gauge",
+        );
+
+        for server_config in servers {
+            for mount in self.state.disk_state(server_config.name())?.mounts() {
+                metrics.sample(
+                    "pg_status_disk_mount_capacity_bytes",
+                    &[("server", server_config.name()), ("mount", mount.path())],
+                    mount.capacity(),
+                );
+            }
+        }
+
+        metrics.family(
+            "pg_status_disk_mount_used_bytes",
+            "Used space of mount point reported by disk command in bytes.",
+            "gauge",
+        );
+
+        for server_config in servers {
+            for mount in self.state.disk_state(server_config.name())?.mounts() {
+                if let Some(used) = mount.used() {
+                    metrics.sample(
+                        "pg_status_disk_mount_used_bytes",
+                        &[("server", server_config.name()), ("mount", mount.path())],
+                        used,
+                    );
+                }
+            }
+        }
+
+        metrics.family(
+            "pg_status_worker_last_success_timestamp",
+            "Time of the last successful update in seconds since UNIX epoch.",
+            "gauge",
+        );
+
+        for server_config in servers {
+            let status = self.state.worker_status(server_config.name())?;
+
+            if let Some(last_success) = status.last_success() {
+                metrics.sample(
+                    "pg_status_worker_last_success_timestamp",
+                    &[("server", server_config.name())],
+                    last_success,
+                );
+            }
+        }
+
+        metrics.family(
+            "pg_status_worker_stage_last_success_timestamp",
+            "Time of the last successful run of update stage in seconds since UNIX epoch.",
+            "gauge",
+        );
+
+        for server_config in servers {
+            let status = self.state.worker_status(server_config.name())?;
+
+            for stage in &WorkerStage::ALL {
+                if let Some(last_success) = status.stage(*stage).last_success() {
+                    metrics.sample(
+                        "pg_status_worker_stage_last_success_timestamp",
+                        &[("server", server_config.name()), ("stage", stage.name())],
+                        last_success,
+                    );
+                }
+            }
+        }
+
+        metrics.family(
+            "pg_status_worker_cycle_duration_seconds",
+            "Duration of the last update cycle in seconds.",
+            "gauge",
+        );
+
+        for server_config in servers {
+            let status = self.state.worker_status(server_config.name())?;
+
+            if let Some(cycle_duration) = status.cycle_duration() {
+                metrics.sample(
+                    "pg_status_worker_cycle_duration_seconds",
+                    &[("server", server_config.name())],
+                    cycle_duration,
+                );
+            }
+        }
+
+        metrics.family(
+            "pg_status_worker_errors_total",
+            "Number of failed update stages.",
+            "counter",
+        );
+
+        for server_config in servers {
+            let status = self.state.worker_status(server_config.name())?;
+
+            for stage in &WorkerStage::ALL {
+                metrics.sample(
+                    "pg_status_worker_errors_total",
+                    &[("server", server_config.name()), ("stage", stage.name())],
+                    status.stage(*stage).errors(),
+                );
+            }
+        }
+
+        metrics.family(
+            "pg_status_worker_consecutive_failures",
+            "Number of update stage failures since its last successful run.",
+            "gauge",
+        );
+
+        for server_config in servers {
+            let status = self.state.worker_status(server_config.name())?;
+
+            for stage in &WorkerStage::ALL {
+                metrics.sample(
+                    "pg_status_worker_consecutive_failures",
+                    &[("server", server_config.name()), ("stage", stage.name())],
+                    status.stage(*stage).consecutive_failures(),
+                );
+            }
+        }
+
+        metrics.family(
+            "pg_status_worker_timeouts_total",
+            "Number of update stages failed by timeout.",
+            "counter",
+        );
+
+        for server_config in servers {
+            let status = self.state.worker_status(server_config.name())?;
+
+            for (kind, timeouts) in &[
+                ("connect", status.connect_timeouts()),
+                ("statement", status.statement_timeouts()),
+                ("disk_command", status.disk_command_timeouts()),
+            ] {
+                metrics.sample(
+                    "pg_status_worker_timeouts_total",
+                    &[("server", server_config.name()), ("kind", kind)],
+                    timeouts,
+                );
+            }
+        }
+
+        Ok(metrics.into_inner())
+    }
+
+    fn render_disk<F>(
+        &self,
+        metrics: &mut Metrics,
+        servers: &[ServerConfig],
+        name: &str,
+        help: &str,
+        callback: F,
+    ) -> StateResult<()>
+    where
+        F: Fn(&ServerConfig) -> StateResult<u64>,
+    {
+        metrics.family(name, help, "gauge");
+
+        for server_config in servers {
+            metrics.sample(
+                name,
+                &[("server", server_config.name())],
+                callback(server_config)?,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl Handler for MetricsHandler {
+    fn handle(&self, _request: &mut IronRequest) -> IronResult<IronResponse> {
+        match self.render() {
+            Ok(body) => {
+                let content_type = Mime(
+                    TopLevel::Text,
+                    SubLevel::Plain,
+                    vec![
+                        (Attr::Ext("version".into()), Value::Ext("0.0.4".into())),
+                        (Attr::Charset, Value::Utf8),
+                    ],
+                );
+
+                Ok(IronResponse::with((status::Ok, content_type, body)))
+            }
+            Err(err) => Ok(IronResponse::with((
+                status::InternalServerError,
+                format!("{}", err),
+            ))),
+        }
+    }
+}
+
+/// Writer for Prometheus text exposition format.
+#[derive(Debug, Default)]
+struct Metrics {
+    buffer: String,
+}
+
+impl Metrics {
+    fn family(&mut self, name: &str, help: &str, kind: &str) {
+        let _ = writeln!(self.buffer, "# HELP {} {}", name, help);
+        let _ = writeln!(self.buffer, "# TYPE {} {}", name, kind);
+    }
+
+    fn sample<T>(&mut self, name: &str, labels: &[(&str, &str)], value: T)
+    where
+        T: Display,
+    {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(label, value)| format!("{}=\"{}\"", label, escape_label(value)))
+            .collect();
+
+        let _ = writeln!(self.buffer, "{}{{{}}} {}", name, labels.join(","), value);
+    }
+
+    fn into_inner(self) -> String {
+        self.buffer
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_label_keeps_plain_value() {
+        assert_eq!(escape_label("app_db-1"), "app_db-1");
+    }
+
+    #[test]
+    fn escape_label_escapes_special_characters() {
+        assert_eq!(escape_label("a\\b"), "a\\\\b");
+        assert_eq!(escape_label("say \"hi\""), "say \\\"hi\\\"");
+        assert_eq!(escape_label("line\nbreak"), "line\\nbreak");
+    }
+
+    #[test]
+    fn escape_label_escapes_backslash_before_quote() {
+        assert_eq!(escape_label("\\\""), "\\\\\\\"");
+    }
+}
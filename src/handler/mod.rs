@@ -1,11 +1,39 @@
+mod audit;
+mod bloat;
 mod dropdb;
 mod error;
+mod export;
+mod health;
+mod history;
+mod https_redirect;
+mod metrics;
+mod reload;
+mod retention;
+mod servers;
+mod sessions;
 mod state;
+mod tables;
 mod update;
+mod users;
 mod util;
 
+pub use self::audit::AuditHandler;
+pub use self::bloat::BloatHandler;
 pub use self::dropdb::DropDbHandler;
 pub use self::error::HandlerError;
 pub use self::error::HandlerResult;
+pub use self::export::ExportHandler;
+pub use self::health::HealthHandler;
+pub use self::history::HistoryHandler;
+pub use self::https_redirect::HttpsRedirectHandler;
+pub use self::metrics::MetricsHandler;
+pub use self::reload::ReloadHandler;
+pub use self::retention::RetentionPlanHandler;
+pub use self::servers::ServersHandler;
+pub use self::sessions::SessionsHandler;
+pub use self::state::server_state;
+pub use self::state::StateData;
 pub use self::state::StateHandler;
+pub use self::tables::TablesHandler;
 pub use self::update::UpdateHandler;
+pub use self::users::UsersHandler;
@@ -0,0 +1,49 @@
+use super::util::handle_empty;
+use super::HandlerError;
+use super::HandlerResult;
+use crate::auth;
+use crate::auth::AuthUser;
+use crate::config::SharedConfig;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+
+#[derive(Debug)]
+pub struct ReloadHandler {
+    config: SharedConfig,
+}
+
+impl ReloadHandler {
+    pub fn new(config: SharedConfig) -> ReloadHandler {
+        ReloadHandler { config }
+    }
+}
+
+impl Handler for ReloadHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        let user = request.extensions.get::<AuthUser>();
+
+        // Only administrators can reload configuration, so without authentication reload is available only by SIGHUP
+        // or file modification.
+        if config.authentication().is_none() {
+            return handle_empty(|| -> HandlerResult<()> {
+                Err(HandlerError::new("Authentication is not configured"))
+            });
+        }
+
+        if !user.is_some_and(AuthUser::is_admin) {
+            return Ok(auth::unauthorized(&config));
+        }
+
+        handle_empty(move || {
+            self.config.reload().map_err(|err| {
+                HandlerError::new(&format!(
+                    "Failed to reload configuration, previous one stays active - {}",
+                    err
+                ))
+            })
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use super::util::handle_request;
+use super::util::select_server;
+use super::HandlerError;
+use crate::clock;
+use crate::config::SharedConfig;
+use crate::state::StateRef;
+use crate::worker;
+use crate::worker::Candidate;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+
+#[derive(Debug)]
+pub struct RetentionPlanHandler {
+    config: SharedConfig,
+    state: StateRef,
+}
+
+impl RetentionPlanHandler {
+    pub fn new(config: SharedConfig, state: StateRef) -> RetentionPlanHandler {
+        RetentionPlanHandler { config, state }
+    }
+}
+
+impl Handler for RetentionPlanHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        handle_request(request, move |request: Request| {
+            let server_config = select_server(&config, request.server.as_deref())?;
+            let retention = config
+                .retention()
+                .ok_or_else(|| HandlerError::new("Retention is not configured"))?;
+            let candidates = worker::retention_plan(
+                retention,
+                server_config,
+                &self.state,
+                clock::unix_timestamp(),
+            )
+            .map_err(|err| HandlerError::new(&format!("{}", err)))?;
+
+            Ok(Response::new(retention.dry_run(), candidates))
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    server: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    dry_run: bool,
+    candidates: Vec<Candidate>,
+}
+
+impl Response {
+    fn new(dry_run: bool, candidates: Vec<Candidate>) -> Response {
+        Response {
+            dry_run,
+            candidates,
+        }
+    }
+}
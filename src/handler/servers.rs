@@ -0,0 +1,49 @@
+use super::util::handle_empty;
+use crate::config::SharedConfig;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+
+#[derive(Debug)]
+pub struct ServersHandler {
+    config: SharedConfig,
+}
+
+impl ServersHandler {
+    pub fn new(config: SharedConfig) -> ServersHandler {
+        ServersHandler { config }
+    }
+}
+
+impl Handler for ServersHandler {
+    fn handle(&self, _request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        handle_empty(move || {
+            let servers: Vec<ServerData> = config
+                .servers()
+                .iter()
+                .map(|server| ServerData::new(server.name(), server.host(), server.port()))
+                .collect();
+
+            Ok(servers)
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ServerData {
+    name: String,
+    host: String,
+    port: u16,
+}
+
+impl ServerData {
+    fn new(name: &str, host: &str, port: u16) -> ServerData {
+        ServerData {
+            name: name.into(),
+            host: host.into(),
+            port,
+        }
+    }
+}
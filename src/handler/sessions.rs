@@ -0,0 +1,68 @@
+use super::util::handle_empty;
+use super::util::query_params;
+use super::util::route_param;
+use super::util::select_server;
+use super::util::unknown_database;
+use super::HandlerError;
+use crate::auth;
+use crate::auth::AuthUser;
+use crate::config::SharedConfig;
+use crate::postgres::PostgresRef;
+use crate::postgres::Session;
+use crate::state::StateRef;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+
+#[derive(Debug)]
+pub struct SessionsHandler {
+    config: SharedConfig,
+    state: StateRef,
+    postgres: PostgresRef,
+}
+
+impl SessionsHandler {
+    pub fn new(config: SharedConfig, state: StateRef, postgres: PostgresRef) -> SessionsHandler {
+        SessionsHandler {
+            config,
+            state,
+            postgres,
+        }
+    }
+}
+
+impl Handler for SessionsHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        let params = query_params(request);
+        let name = route_param(request, "name");
+        let privileged = auth::is_privileged(&config, request.extensions.get::<AuthUser>());
+
+        if let Some(response) = unknown_database(&config, &self.state, &params, name.as_deref()) {
+            return Ok(response);
+        }
+
+        handle_empty(move || {
+            let name = name.ok_or_else(|| HandlerError::new("Database name is required"))?;
+            let server_config = select_server(&config, params.get("server").map(String::as_str))?;
+            let postgres = self.postgres.server(server_config);
+
+            let sessions = postgres.sessions(&name).map_err(|err| {
+                HandlerError::new(&format!(
+                    "Failed to query sessions of database `{}` - {}",
+                    name, err
+                ))
+            })?;
+
+            if privileged {
+                Ok(sessions)
+            } else {
+                Ok(sessions
+                    .into_iter()
+                    .map(Session::without_client_address)
+                    .collect())
+            }
+        })
+    }
+}
@@ -1,8 +1,20 @@
-use super::util::handle_empty;
+use super::util::handle_request;
+use super::util::select_server;
 use super::HandlerError;
+use super::HandlerResult;
+use crate::auth;
+use crate::auth::AuthUser;
+use crate::clock;
 use crate::config::ConfigRef;
-use crate::config::DiskConfig;
+use crate::config::ServerConfig;
+use crate::config::SharedConfig;
+use crate::postgres::SessionStats;
+use crate::quota;
+use crate::state::Database;
+use crate::state::DiskState;
+use crate::state::Mount;
 use crate::state::StateRef;
+use crate::state::WorkerStatus;
 use iron::middleware::Handler;
 use iron::IronResult;
 use iron::Request as IronRequest;
@@ -10,104 +22,207 @@ use iron::Response as IronResponse;
 
 #[derive(Debug)]
 pub struct StateHandler {
-    config: ConfigRef,
+    config: SharedConfig,
     state: StateRef,
 }
 
 impl StateHandler {
-    pub fn new(config: ConfigRef, state: StateRef) -> StateHandler {
+    pub fn new(config: SharedConfig, state: StateRef) -> StateHandler {
         StateHandler { config, state }
     }
 }
 
 impl Handler for StateHandler {
-    fn handle(&self, _request: &mut IronRequest) -> IronResult<IronResponse> {
-        handle_empty(move || {
-            let service_databases = self.config.server().service_databases();
-            let mut databases: Vec<DatabaseData> = Vec::new();
-
-            self.state
-                .for_each(|database| {
-                    databases.push(DatabaseData::new(
-                        database.name(),
-                        database.user(),
-                        database.modified(),
-                        database.size(),
-                        service_databases.contains(database.name()),
-                    ))
-                })
-                .map_err(|_| HandlerError::new("State error"))?;
-
-            let disk = self
-                .state
-                .disk_state()
-                .map_err(|_| HandlerError::new("State error"))?;
-            let used: u64 = match self.config.server().disk() {
-                DiskConfig::Fixed { .. } => databases.iter().map(|d| d.size).sum(),
-                DiskConfig::Command { .. } => 0,
-            };
-
-            Ok(Response::new(
-                disk.offset() + used,
-                disk.capacity(),
-                disk.soft_threshold(),
-                disk.hard_threshold(),
-                databases,
-            ))
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        let user = request.extensions.get::<AuthUser>().cloned();
+
+        handle_request(request, move |request: Request| {
+            let server_config = select_server(&config, request.server.as_deref())?;
+            let data = server_state(&config, &self.state, server_config)?;
+
+            // Client addresses are shown only to users who can read audit log.
+            if auth::is_privileged(&config, user.as_ref()) {
+                Ok(data)
+            } else {
+                Ok(data.without_client_addresses())
+            }
         })
     }
 }
 
+/// Collect state of the server in the same form as it is returned by state API.
+pub fn server_state(
+    config: &ConfigRef,
+    state: &StateRef,
+    server_config: &ServerConfig,
+) -> HandlerResult<StateData> {
+    let server_name = server_config.name();
+    let service_databases = server_config.service_databases();
+    let over_quota_users =
+        quota::over_quota_users(config, state).map_err(|_| HandlerError::new("State error"))?;
+    let mut databases: Vec<DatabaseData> = Vec::new();
+
+    state
+        .for_each(server_name, |database| {
+            databases.push(DatabaseData::new(
+                database,
+                service_databases.contains(database.name()),
+                database
+                    .user()
+                    .is_some_and(|user| over_quota_users.contains(user)),
+            ))
+        })
+        .map_err(|_| HandlerError::new("State error"))?;
+
+    let disk = state
+        .disk_state(server_name)
+        .map_err(|_| HandlerError::new("State error"))?;
+    let used = state
+        .disk_used(server_config)
+        .map_err(|_| HandlerError::new("State error"))?;
+    let worker = state
+        .worker_status(server_name)
+        .map_err(|_| HandlerError::new("State error"))?;
+    let stale = state
+        .is_stale(server_name, clock::unix_timestamp(), config.stale_after())
+        .map_err(|_| HandlerError::new("State error"))?;
+
+    Ok(StateData::new(used, &disk, databases, stale, worker))
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    server: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
-struct Response {
+pub struct StateData {
     disk_used: u64,
     disk_capacity: u64,
     soft_threshold: u64,
     hard_threshold: u64,
+    disk_mounts: Vec<Mount>,
     databases: Vec<DatabaseData>,
+    updated: Option<i64>,
+    stale: bool,
+    worker: WorkerStatus,
 }
 
-impl Response {
+impl StateData {
     fn new(
         disk_used: u64,
-        disk_capacity: u64,
-        soft_threshold: u64,
-        hard_threshold: u64,
+        disk: &DiskState,
         databases: Vec<DatabaseData>,
-    ) -> Response {
-        Response {
+        stale: bool,
+        worker: WorkerStatus,
+    ) -> StateData {
+        StateData {
             disk_used,
-            disk_capacity,
-            soft_threshold,
-            hard_threshold,
+            disk_capacity: disk.capacity(),
+            soft_threshold: disk.soft_threshold(),
+            hard_threshold: disk.hard_threshold(),
+            disk_mounts: disk.mounts().to_vec(),
             databases,
+            updated: worker.data_updated(),
+            stale,
+            worker,
         }
     }
+
+    pub fn databases(&self) -> &[DatabaseData] {
+        &self.databases
+    }
+
+    /// Returns state with hidden client addresses of sessions.
+    pub fn without_client_addresses(mut self) -> StateData {
+        for database in &mut self.databases {
+            database.sessions = database.sessions.clone().without_client_addresses();
+        }
+
+        self
+    }
 }
 
 #[derive(Debug, Serialize)]
-struct DatabaseData {
+pub struct DatabaseData {
     name: String,
     user: Option<String>,
+    attribution: Option<String>,
     modified: i64,
+    last_active: Option<i64>,
     size: u64,
+    reclaimable: Option<u64>,
     service: bool,
+    over_quota: bool,
+    owner: String,
+    comment: Option<String>,
+    encoding: String,
+    collation: String,
+    ctype: String,
+    tablespace: Option<String>,
+    connection_limit: i32,
+    allow_connections: bool,
+    is_template: bool,
+    sessions: SessionStats,
 }
 
 impl DatabaseData {
-    fn new(
-        name: &str,
-        user: Option<&String>,
-        modified: i64,
-        size: u64,
-        service: bool,
-    ) -> DatabaseData {
+    fn new(database: &Database, service: bool, over_quota: bool) -> DatabaseData {
+        let info = database.info();
+
         DatabaseData {
-            name: name.into(),
-            user: user.cloned(),
-            modified,
-            size,
+            name: database.name().into(),
+            user: database.user().cloned(),
+            attribution: database.attribution().cloned(),
+            modified: database.modified(),
+            last_active: database.last_active(),
+            size: database.size(),
+            reclaimable: database.reclaimable(),
             service,
+            over_quota,
+            owner: info.owner().into(),
+            comment: info.comment().map(String::from),
+            encoding: info.encoding().into(),
+            collation: info.collation().into(),
+            ctype: info.ctype().into(),
+            tablespace: info.tablespace().map(String::from),
+            connection_limit: info.connection_limit(),
+            allow_connections: info.allow_connections(),
+            is_template: info.is_template(),
+            sessions: database.sessions().clone(),
         }
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    pub fn modified(&self) -> i64 {
+        self.modified
+    }
+
+    pub fn last_active(&self) -> Option<i64> {
+        self.last_active
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn service(&self) -> bool {
+        self.service
+    }
+
+    pub fn over_quota(&self) -> bool {
+        self.over_quota
+    }
 }
@@ -0,0 +1,71 @@
+use super::util::handle_empty;
+use super::util::query_params;
+use super::util::route_param;
+use super::util::select_server;
+use super::util::unknown_database;
+use super::HandlerError;
+use crate::clock;
+use crate::config::SharedConfig;
+use crate::postgres::PostgresRef;
+use crate::state::StateRef;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+
+#[derive(Debug)]
+pub struct TablesHandler {
+    config: SharedConfig,
+    state: StateRef,
+    postgres: PostgresRef,
+}
+
+impl TablesHandler {
+    pub fn new(config: SharedConfig, state: StateRef, postgres: PostgresRef) -> TablesHandler {
+        TablesHandler {
+            config,
+            state,
+            postgres,
+        }
+    }
+}
+
+impl Handler for TablesHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        let params = query_params(request);
+        let name = route_param(request, "name");
+
+        if let Some(response) = unknown_database(&config, &self.state, &params, name.as_deref()) {
+            return Ok(response);
+        }
+
+        handle_empty(move || {
+            let name = name.ok_or_else(|| HandlerError::new("Database name is required"))?;
+            let server_config = select_server(&config, params.get("server").map(String::as_str))?;
+            let now = clock::unix_timestamp();
+            let cached = self
+                .state
+                .tables(server_config.name(), &name, now, config.update_interval())
+                .map_err(|_| HandlerError::new("State error"))?;
+
+            if let Some(tables) = cached {
+                return Ok(tables);
+            }
+
+            let postgres = self.postgres.server(server_config);
+            let tables = postgres.table_sizes(&name).map_err(|err| {
+                HandlerError::new(&format!(
+                    "Failed to query tables of database `{}` - {}",
+                    name, err
+                ))
+            })?;
+
+            self.state
+                .set_tables(server_config.name(), &name, tables.clone(), now)
+                .map_err(|_| HandlerError::new("State error"))?;
+
+            Ok(tables)
+        })
+    }
+}
@@ -1,7 +1,10 @@
-use super::util::handle_empty;
+use super::util::handle_request;
+use super::util::select_server;
 use super::HandlerError;
-use crate::config::ConfigRef;
+use crate::config::SharedConfig;
+use crate::postgres::PostgresRef;
 use crate::state::StateRef;
+use crate::state::WorkerStage;
 use crate::worker;
 use iron::middleware::Handler;
 use iron::IronResult;
@@ -10,23 +13,43 @@ use iron::Response as IronResponse;
 
 #[derive(Debug)]
 pub struct UpdateHandler {
-    config: ConfigRef,
+    config: SharedConfig,
     state: StateRef,
+    postgres: PostgresRef,
 }
 
 impl UpdateHandler {
-    pub fn new(config: ConfigRef, state: StateRef) -> UpdateHandler {
-        UpdateHandler { config, state }
+    pub fn new(config: SharedConfig, state: StateRef, postgres: PostgresRef) -> UpdateHandler {
+        UpdateHandler {
+            config,
+            state,
+            postgres,
+        }
     }
 }
 
 impl Handler for UpdateHandler {
-    fn handle(&self, _request: &mut IronRequest) -> IronResult<IronResponse> {
-        handle_empty(move || {
-            worker::update_disk(&self.config, &self.state)
-                .map_err(|err| HandlerError::new(&format!("{}", err)))?;
-            worker::update_databases(&self.config, &self.state)
-                .map_err(|err| HandlerError::new(&format!("{}", err)))
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        handle_request(request, move |request: Request| {
+            let server_config = select_server(&config, request.server.as_deref())?;
+            let postgres = self.postgres.server(server_config);
+
+            let server_name = server_config.name();
+
+            let result = worker::update_disk(server_config, &self.state);
+            worker::record_stage(&self.state, server_name, WorkerStage::Disk, &result);
+            result.map_err(|err| HandlerError::new(&format!("{}", err)))?;
+
+            let result = worker::update_databases(&config, server_config, &postgres, &self.state)
+                .and_then(|_| worker::save_activity(&config, &self.state));
+            worker::record_stage(&self.state, server_name, WorkerStage::Databases, &result);
+            result.map_err(|err| HandlerError::new(&format!("{}", err)))
         })
     }
 }
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    server: Option<String>,
+}
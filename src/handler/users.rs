@@ -0,0 +1,64 @@
+use super::util::handle_empty;
+use super::HandlerError;
+use crate::config::SharedConfig;
+use crate::quota;
+use crate::quota::QuotaStatus;
+use crate::state::StateRef;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+
+#[derive(Debug)]
+pub struct UsersHandler {
+    config: SharedConfig,
+    state: StateRef,
+}
+
+impl UsersHandler {
+    pub fn new(config: SharedConfig, state: StateRef) -> UsersHandler {
+        UsersHandler { config, state }
+    }
+}
+
+impl Handler for UsersHandler {
+    fn handle(&self, _request: &mut IronRequest) -> IronResult<IronResponse> {
+        let config = self.config.get();
+        handle_empty(move || {
+            let usages = self
+                .state
+                .user_usage()
+                .map_err(|_| HandlerError::new("State error"))?;
+            let mut users = Vec::new();
+
+            for user in config.users() {
+                let login = user.login().to_lowercase();
+                let usage = usages.get(&login).cloned().unwrap_or_default();
+                let quota = user.quota();
+
+                users.push(UserData {
+                    login,
+                    size: usage.size(),
+                    databases: usage.databases(),
+                    quota_size: quota.and_then(|quota| quota.size()),
+                    quota_soft_size: quota.and_then(|quota| quota.soft_size()),
+                    quota_databases: quota.and_then(|quota| quota.databases()),
+                    status: quota::status(quota, &usage),
+                });
+            }
+
+            Ok(users)
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct UserData {
+    login: String,
+    size: u64,
+    databases: u64,
+    quota_size: Option<u64>,
+    quota_soft_size: Option<u64>,
+    quota_databases: Option<u64>,
+    status: QuotaStatus,
+}
@@ -1,16 +1,24 @@
+use super::HandlerError;
 use super::HandlerResult;
+use crate::config::Config;
+use crate::config::ServerConfig;
+use crate::state::StateRef;
 
 use iron::mime::Mime;
 use iron::mime::SubLevel;
 use iron::mime::TopLevel;
 use iron::status;
+use iron::url::percent_encoding::percent_decode;
 use iron::IronResult;
 use iron::Request;
 use iron::Response;
+use router::Router;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use serde_json;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::io::Read;
+use std::str::FromStr;
 
 pub fn handle_empty<Res, F>(callback: F) -> IronResult<Response>
 where
@@ -19,7 +27,7 @@ where
 {
     let response = match callback() {
         Ok(response) => ErrorResponse::success(response),
-        Err(err) => ErrorResponse::error(&format!("{}", err)),
+        Err(err) => ErrorResponse::error(&err),
     };
 
     struct_to_response(&response)
@@ -31,18 +39,98 @@ where
     Res: Serialize,
     F: FnOnce(Req) -> HandlerResult<Res>,
 {
-    let result = match serde_json::from_reader(request.body.by_ref()) {
+    let mut body = String::new();
+
+    if let Err(err) = request.body.read_to_string(&mut body) {
+        return Ok(Response::with((status::BadRequest, format!("{}", err))));
+    }
+
+    // Empty body is accepted as empty object, so requests without parameters may omit body.
+    let body = if body.trim().is_empty() { "{}" } else { &body };
+    let result = match serde_json::from_str(body) {
         Ok(request) => callback(request),
         Err(err) => return Ok(Response::with((status::BadRequest, format!("{}", err)))),
     };
     let response = match result {
         Ok(response) => ErrorResponse::success(response),
-        Err(err) => ErrorResponse::error(&format!("{}", err)),
+        Err(err) => ErrorResponse::error(&err),
     };
 
     struct_to_response(&response)
 }
 
+pub fn find_server<'a>(config: &'a Config, name: &str) -> HandlerResult<&'a ServerConfig> {
+    config
+        .server(name)
+        .ok_or_else(|| HandlerError::new(&format!("Server `{}` not found", name)))
+}
+
+/// Returns server with given name. Name may be omitted if only one server is configured.
+pub fn select_server<'a>(
+    config: &'a Config,
+    name: Option<&str>,
+) -> HandlerResult<&'a ServerConfig> {
+    match name {
+        Some(name) => find_server(config, name),
+        None => match config.servers() {
+            [server_config] => Ok(server_config),
+            _ => Err(HandlerError::new(
+                "Server is required when several servers are configured",
+            )),
+        },
+    }
+}
+
+pub fn query_params(request: &Request) -> HashMap<String, String> {
+    request.url.as_ref().query_pairs().into_owned().collect()
+}
+
+/// Returns percent-decoded value of the named segment of the route matched by router.
+pub fn route_param(request: &Request, name: &str) -> Option<String> {
+    request
+        .extensions
+        .get::<Router>()
+        .and_then(|params| params.find(name))
+        .and_then(|value| percent_decode(value.as_bytes()).decode_utf8().ok())
+        .map(String::from)
+}
+
+/// Returns response with status 404 if the server is known, but the database is not found in its state. Database names
+/// come from request path, so the server is never queried for databases which were not listed by the worker.
+pub fn unknown_database(
+    config: &Config,
+    state: &StateRef,
+    params: &HashMap<String, String>,
+    name: Option<&str>,
+) -> Option<Response> {
+    let server_config = select_server(config, params.get("server").map(String::as_str)).ok()?;
+    let name = name?;
+
+    match state.database(server_config.name(), name) {
+        Ok(Some(_)) => None,
+        Ok(None) => Some(Response::with((
+            status::NotFound,
+            format!("Database `{}` not found", name),
+        ))),
+        Err(_) => Some(Response::with((status::InternalServerError, "State error"))),
+    }
+}
+
+pub fn query_param<T>(params: &HashMap<String, String>, name: &str) -> HandlerResult<Option<T>>
+where
+    T: FromStr,
+{
+    match params.get(name) {
+        Some(value) => value.parse().map(Some).map_err(|_| {
+            HandlerError::new(&format!(
+                "Invalid value `{}` of parameter `{}`",
+                value, name
+            ))
+        }),
+        None => Ok(None),
+    }
+}
+
 fn struct_to_response<Res>(value: &Res) -> IronResult<Response>
 where
     Res: Serialize,
@@ -64,6 +152,8 @@ struct ErrorResponse<T> {
     result: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
     message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
 }
 
 impl<T> ErrorResponse<T>
@@ -75,14 +165,16 @@ where
             success: true,
             result: Some(result),
             message: None,
+            details: None,
         }
     }
 
-    fn error(message: &str) -> ErrorResponse<T> {
+    fn error(error: &HandlerError) -> ErrorResponse<T> {
         ErrorResponse {
             success: false,
             result: None,
-            message: Some(message.into()),
+            message: Some(format!("{}", error)),
+            details: error.details().cloned(),
         }
     }
 }
@@ -0,0 +1,44 @@
+use serde_json::Error as JsonError;
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::io::Error as IoError;
+
+pub type HistoryResult<T> = Result<T, HistoryError>;
+
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum HistoryError {
+    IoError { message: String },
+    FormatError { message: String },
+    LockError,
+}
+
+impl HistoryError {
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn io_error(error: IoError) -> HistoryError {
+        HistoryError::IoError {
+            message: format!("{}", error),
+        }
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn format_error(error: JsonError) -> HistoryError {
+        HistoryError::FormatError {
+            message: format!("{}", error),
+        }
+    }
+}
+
+impl Error for HistoryError {}
+
+impl Display for HistoryError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            HistoryError::IoError { message } => write!(f, "{}", message),
+            HistoryError::FormatError { message } => write!(f, "{}", message),
+            HistoryError::LockError => write!(f, "Failed to acquire history lock"),
+        }
+    }
+}
@@ -0,0 +1,310 @@
+mod error;
+mod sample;
+
+pub use self::error::HistoryError;
+pub use self::error::HistoryResult;
+pub use self::sample::Sample;
+
+use crate::clock;
+use crate::config::DownsampleConfig;
+use crate::config::HistoryConfig;
+use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+const HISTORY_FILE: &str = "history.jsonl";
+const HISTORY_TEMP_FILE: &str = "history.jsonl.tmp";
+// Compaction rewrites the whole file, so it runs once a day instead of every append.
+const COMPACT_INTERVAL: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone)]
+pub struct HistoryRef {
+    inner: Arc<Mutex<History>>,
+}
+
+impl HistoryRef {
+    fn with_history<F, T>(&self, callback: F) -> HistoryResult<T>
+    where
+        F: FnOnce(&mut History) -> HistoryResult<T>,
+    {
+        match self.inner.lock() {
+            Ok(ref mut history) => callback(history),
+            Err(err) => {
+                warn!("Failed to acquire history lock - {}", err);
+
+                Err(HistoryError::LockError)
+            }
+        }
+    }
+
+    /// Append samples to the store. Samples which does not match retention settings are removed once a day.
+    pub fn append(&self, samples: Vec<Sample>, now: i64) -> HistoryResult<()> {
+        self.with_history(move |history| {
+            history.append(samples)?;
+
+            if now - history.compacted >= COMPACT_INTERVAL {
+                history.compact(now)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Call callback for every sample of the server in given time range ordered by time. If database is `None` only
+    /// disk samples will be passed.
+    pub fn for_each<F>(
+        &self,
+        server: &str,
+        database: Option<&str>,
+        from: i64,
+        to: i64,
+        callback: F,
+    ) -> HistoryResult<()>
+    where
+        F: FnMut(&Sample),
+    {
+        self.with_history(move |history| {
+            history
+                .samples
+                .iter()
+                .filter(|sample| sample.server() == server && sample.database() == database)
+                .filter(|sample| sample.time() >= from && sample.time() <= to)
+                .for_each(callback);
+
+            Ok(())
+        })
+    }
+}
+
+#[derive(Debug)]
+struct History {
+    path: PathBuf,
+    retention: u64,
+    downsample: Option<DownsampleConfig>,
+    samples: Vec<Sample>,
+    compacted: i64,
+}
+
+impl History {
+    fn open(config: &HistoryConfig) -> HistoryResult<History> {
+        fs::create_dir_all(config.path()).map_err(HistoryError::io_error)?;
+
+        let path = config.path().join(HISTORY_FILE);
+        let now = clock::unix_timestamp();
+        let border = now - config.retention() as i64;
+        let mut samples = Vec::new();
+        let mut expired = false;
+
+        if path.exists() {
+            let reader = BufReader::new(File::open(&path).map_err(HistoryError::io_error)?);
+
+            for line in reader.lines() {
+                let line = line.map_err(HistoryError::io_error)?;
+
+                // Expired samples are skipped while reading, so they are never held in memory.
+                match serde_json::from_str::<Sample>(&line) {
+                    Ok(sample) if sample.time() >= border => samples.push(sample),
+                    Ok(_) => expired = true,
+                    Err(err) => warn!("Skip invalid history record - {}", err),
+                }
+            }
+        }
+
+        let mut history = History {
+            path,
+            retention: config.retention(),
+            downsample: config.downsample().cloned(),
+            samples,
+            compacted: now,
+        };
+
+        if expired {
+            history.rewrite()?;
+        }
+
+        history.compact(now)?;
+
+        Ok(history)
+    }
+
+    fn append(&mut self, samples: Vec<Sample>) -> HistoryResult<()> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(HistoryError::io_error)?;
+        let mut writer = BufWriter::new(file);
+
+        for sample in &samples {
+            write_sample(&mut writer, sample)?;
+        }
+
+        writer.flush().map_err(HistoryError::io_error)?;
+        self.samples.extend(samples);
+
+        Ok(())
+    }
+
+    fn compact(&mut self, now: i64) -> HistoryResult<()> {
+        self.compacted = now;
+
+        if expire(
+            &mut self.samples,
+            self.retention,
+            self.downsample.as_ref(),
+            now,
+        ) {
+            self.rewrite()?;
+        }
+
+        Ok(())
+    }
+
+    fn rewrite(&self) -> HistoryResult<()> {
+        let temp_path = self.path.with_file_name(HISTORY_TEMP_FILE);
+        let file = File::create(&temp_path).map_err(HistoryError::io_error)?;
+        let mut writer = BufWriter::new(file);
+
+        for sample in &self.samples {
+            write_sample(&mut writer, sample)?;
+        }
+
+        writer.flush().map_err(HistoryError::io_error)?;
+        fs::rename(&temp_path, &self.path).map_err(HistoryError::io_error)
+    }
+}
+
+/// Remove samples older than retention and downsample old samples. Returns `true` if some samples were removed.
+fn expire(
+    samples: &mut Vec<Sample>,
+    retention: u64,
+    downsample: Option<&DownsampleConfig>,
+    now: i64,
+) -> bool {
+    let length = samples.len();
+    let border = now - retention as i64;
+
+    samples.retain(|sample| sample.time() >= border);
+
+    if let Some(downsample) = downsample {
+        let border = now - downsample.after() as i64;
+        let interval = downsample.interval() as i64;
+        let mut buckets = HashSet::new();
+
+        // Samples are ordered by time, so only the first sample of every interval will be kept.
+        samples.retain(|sample| {
+            sample.time() >= border
+                || buckets.insert((
+                    sample.server().to_string(),
+                    sample.database().map(String::from),
+                    sample.time() / interval,
+                ))
+        });
+    }
+
+    samples.len() != length
+}
+
+fn write_sample<W>(writer: &mut W, sample: &Sample) -> HistoryResult<()>
+where
+    W: Write,
+{
+    serde_json::to_writer(writer.by_ref(), sample).map_err(HistoryError::format_error)?;
+    writeln!(writer).map_err(HistoryError::io_error)
+}
+
+pub fn open(config: &HistoryConfig) -> HistoryResult<HistoryRef> {
+    Ok(HistoryRef {
+        inner: Arc::new(Mutex::new(History::open(config)?)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database(name: &str, time: i64) -> Sample {
+        Sample::Database {
+            server: "primary".into(),
+            time,
+            name: name.into(),
+            size: 1,
+        }
+    }
+
+    fn times(samples: &[Sample]) -> Vec<(Option<&str>, i64)> {
+        samples
+            .iter()
+            .map(|sample| (sample.database(), sample.time()))
+            .collect()
+    }
+
+    fn downsample(after: u64, interval: u64) -> DownsampleConfig {
+        serde_yaml::from_str(&format!("after: {}\ninterval: {}", after, interval)).unwrap()
+    }
+
+    #[test]
+    fn expire_removes_samples_older_than_retention() {
+        let mut samples = vec![database("a", 100), database("a", 150), database("a", 200)];
+
+        assert!(expire(&mut samples, 60, None, 210));
+        assert_eq!(times(&samples), vec![(Some("a"), 150), (Some("a"), 200)]);
+    }
+
+    #[test]
+    fn expire_keeps_unchanged_samples() {
+        let mut samples = vec![database("a", 150), database("a", 200)];
+
+        assert!(!expire(&mut samples, 60, None, 210));
+        assert_eq!(samples.len(), 2);
+    }
+
+    #[test]
+    fn expire_keeps_first_old_sample_of_every_interval() {
+        let mut samples = vec![
+            database("a", 100),
+            database("b", 105),
+            database("a", 110),
+            database("a", 120),
+            database("a", 190),
+            database("a", 195),
+        ];
+
+        assert!(expire(&mut samples, 1000, Some(&downsample(20, 20)), 200));
+        assert_eq!(
+            times(&samples),
+            vec![
+                (Some("a"), 100),
+                (Some("b"), 105),
+                (Some("a"), 120),
+                (Some("a"), 190),
+                (Some("a"), 195),
+            ]
+        );
+    }
+
+    #[test]
+    fn expire_downsamples_disk_and_database_series_separately() {
+        let disk = Sample::Disk {
+            server: "primary".into(),
+            time: 101,
+            used: 1,
+            capacity: 2,
+            soft_threshold: 1,
+            hard_threshold: 2,
+            databases: 1,
+        };
+        let mut samples = vec![database("a", 100), disk, database("a", 102)];
+
+        assert!(expire(&mut samples, 1000, Some(&downsample(0, 100)), 200));
+        assert_eq!(times(&samples), vec![(Some("a"), 100), (None, 101)]);
+    }
+}
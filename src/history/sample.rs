@@ -0,0 +1,43 @@
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Sample {
+    Database {
+        server: String,
+        time: i64,
+        name: String,
+        size: u64,
+    },
+    Disk {
+        server: String,
+        time: i64,
+        used: u64,
+        capacity: u64,
+        soft_threshold: u64,
+        hard_threshold: u64,
+        databases: usize,
+    },
+}
+
+impl Sample {
+    pub fn server(&self) -> &str {
+        match self {
+            Sample::Database { server, .. } => server,
+            Sample::Disk { server, .. } => server,
+        }
+    }
+
+    pub fn time(&self) -> i64 {
+        match self {
+            Sample::Database { time, .. } => *time,
+            Sample::Disk { time, .. } => *time,
+        }
+    }
+
+    /// Returns database name for database samples or `None` for disk samples.
+    pub fn database(&self) -> Option<&str> {
+        match self {
+            Sample::Database { name, .. } => Some(name),
+            Sample::Disk { .. } => None,
+        }
+    }
+}
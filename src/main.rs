@@ -4,17 +4,27 @@ extern crate log;
 #[macro_use]
 extern crate serde_derive;
 
+mod audit;
+mod auth;
+mod cli;
+mod clock;
 mod config;
+mod cors;
 mod error;
 mod handler;
+mod history;
 mod options;
 mod postgres;
+mod quota;
 mod server;
 mod state;
+mod table;
+mod tls;
 mod worker;
 
 use crate::error::ApplicationError;
 use crate::error::ApplicationResult;
+use crate::options::Command;
 use crate::options::Options;
 use structopt::StructOpt;
 
@@ -25,12 +35,71 @@ fn main() -> ApplicationResult {
     let config =
         config::load(options.config_path()).map_err(ApplicationError::read_config_error)?;
 
+    if options.check_config() {
+        return check_config(&config);
+    }
+
     config::validate(config.clone()).map_err(ApplicationError::config_error)?;
 
+    match options.command() {
+        Command::Serve => serve(&options, config),
+        command => cli::run(command, config),
+    }
+}
+
+/// Start worker and web server. Configuration is reloaded when its file changes.
+fn serve(options: &Options, config: config::ConfigRef) -> ApplicationResult {
     let state = state::create();
+    let postgres = postgres::create();
+    let history = match config.history() {
+        Some(history_config) => {
+            Some(history::open(history_config).map_err(ApplicationError::history_error)?)
+        }
+        None => None,
+    };
+    let audit = match config.audit() {
+        Some(audit_config) => {
+            Some(audit::open(audit_config).map_err(ApplicationError::audit_error)?)
+        }
+        None => None,
+    };
+    let tls = match config.https() {
+        Some(https_config) => Some(tls::create(https_config).map_err(ApplicationError::tls_error)?),
+        None => None,
+    };
+
+    let config = config::SharedConfig::new(options.config_path(), config);
+
+    config::watch(config.clone());
+    worker::start(
+        config.clone(),
+        state.clone(),
+        postgres.clone(),
+        history.clone(),
+        audit.clone(),
+    );
+    server::start(options, config, state, postgres, history, audit, tls);
+
+    Ok(())
+}
+
+/// Print every problem of configuration. Fails if configuration has errors.
+fn check_config(config: &config::Config) -> ApplicationResult {
+    let issues = config::check(config);
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity() == config::Severity::Error)
+        .count();
+
+    for issue in &issues {
+        println!("{}", issue);
+    }
+
+    if errors > 0 {
+        return Err(ApplicationError::invalid_config(errors));
+    }
 
-    worker::start(config.clone(), state.clone());
-    server::start(&options, config, state);
+    println!("Configuration is valid");
 
     Ok(())
 }
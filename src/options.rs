@@ -1,5 +1,6 @@
 use std::path::Path;
 use std::path::PathBuf;
+use std::str::FromStr;
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
@@ -32,6 +33,15 @@ pub struct Options {
         default_value = "8080"
     )]
     port: u16,
+
+    #[structopt(
+        long = "check-config",
+        help = "Check configuration file, print found problems and exit"
+    )]
+    check_config: bool,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
 }
 
 impl Options {
@@ -46,4 +56,96 @@ impl Options {
     pub fn port(&self) -> u16 {
         self.port
     }
+
+    pub fn check_config(&self) -> bool {
+        self.check_config
+    }
+
+    /// Returns command to execute, web server is started if command is not given.
+    pub fn command(&self) -> &Command {
+        self.command.as_ref().unwrap_or(&Command::Serve)
+    }
+}
+
+#[derive(StructOpt, Debug)]
+pub enum Command {
+    #[structopt(name = "serve", about = "Start web server (default)")]
+    Serve,
+
+    #[structopt(name = "list", about = "Query databases and print them")]
+    List {
+        #[structopt(
+            short = "s",
+            long = "server",
+            name = "SERVER",
+            help = "Print databases of given server only"
+        )]
+        server: Option<String>,
+
+        #[structopt(
+            short = "f",
+            long = "format",
+            name = "FORMAT",
+            help = "Output format: table, json or csv",
+            default_value = "table"
+        )]
+        format: Format,
+    },
+
+    #[structopt(name = "drop", about = "Drop database")]
+    Drop {
+        #[structopt(name = "NAME", help = "Name of database to drop")]
+        name: String,
+
+        #[structopt(
+            short = "s",
+            long = "server",
+            name = "SERVER",
+            help = "Server of database, required if several servers are configured"
+        )]
+        server: Option<String>,
+
+        #[structopt(long = "force", help = "Terminate active sessions of database")]
+        force: bool,
+    },
+
+    #[structopt(name = "disk", about = "Update disk state once and print it")]
+    Disk {
+        #[structopt(
+            short = "s",
+            long = "server",
+            name = "SERVER",
+            help = "Print disk state of given server only"
+        )]
+        server: Option<String>,
+
+        #[structopt(
+            short = "f",
+            long = "format",
+            name = "FORMAT",
+            help = "Output format: table, json or csv",
+            default_value = "table"
+        )]
+        format: Format,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Format, String> {
+        match value {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown format {}", value)),
+        }
+    }
 }
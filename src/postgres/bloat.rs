@@ -0,0 +1,42 @@
+use postgres::Row;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BloatMethod {
+    /// Estimated from planner statistics.
+    Estimate,
+    /// Measured with `pgstattuple` extension.
+    Pgstattuple,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Bloat {
+    schema: String,
+    table: String,
+    index: Option<String>,
+    size: u64,
+    wasted: u64,
+    method: BloatMethod,
+}
+
+impl Bloat {
+    /// Create bloat from row of `table_bloat.sql`, `table_bloat_pgstattuple.sql` or `index_bloat.sql` query.
+    pub fn from_row(row: &Row, method: BloatMethod) -> Bloat {
+        let size: i64 = row.get(3);
+        let wasted: i64 = row.get(4);
+
+        Bloat {
+            schema: row.get(0),
+            table: row.get(1),
+            index: row.get(2),
+            size: size as u64,
+            wasted: wasted.max(0) as u64,
+            method,
+        }
+    }
+
+    /// Returns estimated number of bytes which can be reclaimed by `VACUUM FULL` or `REINDEX`.
+    pub fn wasted(&self) -> u64 {
+        self.wasted
+    }
+}
@@ -0,0 +1,176 @@
+use super::DatabaseError;
+use super::DatabaseResult;
+use crate::config::ServerConfig;
+use crate::config::SslMode;
+use native_tls::Certificate;
+use native_tls::Identity;
+use native_tls::TlsConnector;
+use postgres::config::SslMode as PgSslMode;
+use postgres::Client;
+use postgres::NoTls;
+use postgres_native_tls::MakeTlsConnector;
+use r2d2::ManageConnection;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Number of unanswered keepalive probes after which connection is considered broken.
+const KEEPALIVE_RETRIES: u32 = 3;
+
+/// Application name of connections made by the application, so they are not reported as sessions of databases.
+pub const APPLICATION_NAME: &str = "postgres-status";
+
+/// Parameters of connections to the server.
+#[derive(Debug, PartialEq)]
+pub struct ConnectionSettings {
+    server: String,
+    port: u16,
+    user: String,
+    password: String,
+    ssl_mode: SslMode,
+    ssl_root_cert: Option<PathBuf>,
+    ssl_cert: Option<PathBuf>,
+    ssl_key: Option<PathBuf>,
+    connect_timeout: Duration,
+    statement_timeout: u64,
+}
+
+impl ConnectionSettings {
+    pub fn new(server_config: &ServerConfig) -> ConnectionSettings {
+        ConnectionSettings {
+            server: server_config.host().into(),
+            port: server_config.port(),
+            user: server_config.role().into(),
+            password: server_config.password().into(),
+            ssl_mode: server_config.ssl_mode(),
+            ssl_root_cert: server_config.ssl_root_cert().map(PathBuf::from),
+            ssl_cert: server_config.ssl_cert().map(PathBuf::from),
+            ssl_key: server_config.ssl_key().map(PathBuf::from),
+            connect_timeout: Duration::from_secs(server_config.connect_timeout()),
+            statement_timeout: server_config.statement_timeout(),
+        }
+    }
+
+    pub fn connect(&self, database_name: &str) -> DatabaseResult<Client> {
+        let mut config = Client::configure();
+
+        config
+            .host(&self.server)
+            .port(self.port)
+            .user(&self.user)
+            .password(&self.password)
+            .dbname(database_name)
+            .application_name(APPLICATION_NAME)
+            .connect_timeout(self.connect_timeout)
+            .options(&format!("-c statement_timeout={}s", self.statement_timeout));
+
+        // Statement timeout is enforced by the server itself. Connections to the server which stopped responding are
+        // closed by keepalive probes and by timeout of unacknowledged data after a few connect timeouts.
+        config
+            .keepalives(true)
+            .keepalives_idle(self.connect_timeout)
+            .keepalives_interval(self.connect_timeout)
+            .keepalives_retries(KEEPALIVE_RETRIES)
+            .tcp_user_timeout(self.connect_timeout * (KEEPALIVE_RETRIES + 1));
+
+        match self.ssl_mode {
+            SslMode::Disable => config.ssl_mode(PgSslMode::Disable).connect(NoTls),
+            SslMode::Prefer => config
+                .ssl_mode(PgSslMode::Prefer)
+                .connect(self.tls_connector()?),
+            SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull => config
+                .ssl_mode(PgSslMode::Require)
+                .connect(self.tls_connector()?),
+        }
+        .map_err(DatabaseError::connection_error)
+    }
+
+    /// Returns maximal time of connection attempt.
+    pub fn connect_timeout(&self) -> Duration {
+        self.connect_timeout
+    }
+
+    fn tls_connector(&self) -> DatabaseResult<MakeTlsConnector> {
+        let mut builder = TlsConnector::builder();
+
+        match self.ssl_mode {
+            // As in libpq, mode `require` verifies certificate authority if root certificates are given.
+            SslMode::Require if self.ssl_root_cert.is_some() => {
+                builder.danger_accept_invalid_hostnames(true);
+            }
+            SslMode::Disable | SslMode::Prefer | SslMode::Require => {
+                builder.danger_accept_invalid_certs(true);
+            }
+            SslMode::VerifyCa => {
+                builder.danger_accept_invalid_hostnames(true);
+            }
+            SslMode::VerifyFull => {}
+        }
+
+        if let Some(ref path) = self.ssl_root_cert {
+            let certificates = Certificate::stack_from_pem(&read_file(path)?)
+                .map_err(|err| DatabaseError::tls_error(&format!("{}: {}", path.display(), err)))?;
+
+            for certificate in certificates {
+                builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let (Some(ref cert_path), Some(ref key_path)) = (&self.ssl_cert, &self.ssl_key) {
+            let identity = Identity::from_pkcs8(&read_file(cert_path)?, &read_file(key_path)?)
+                .map_err(|err| {
+                    DatabaseError::tls_error(&format!("{}: {}", cert_path.display(), err))
+                })?;
+
+            builder.identity(identity);
+        }
+
+        let connector = builder
+            .build()
+            .map_err(|err| DatabaseError::tls_error(&format!("{}", err)))?;
+
+        Ok(MakeTlsConnector::new(connector))
+    }
+}
+
+fn read_file(path: &Path) -> DatabaseResult<Vec<u8>> {
+    fs::read(path).map_err(|err| DatabaseError::tls_error(&format!("{}: {}", path.display(), err)))
+}
+
+/// Creates and checks connections to one database of the server.
+#[derive(Debug)]
+pub struct ConnectionManager {
+    settings: Arc<ConnectionSettings>,
+    database_name: String,
+}
+
+impl ConnectionManager {
+    pub fn new(settings: Arc<ConnectionSettings>, database_name: &str) -> ConnectionManager {
+        ConnectionManager {
+            settings,
+            database_name: database_name.into(),
+        }
+    }
+}
+
+impl ManageConnection for ConnectionManager {
+    type Connection = Client;
+    type Error = DatabaseError;
+
+    fn connect(&self) -> DatabaseResult<Client> {
+        self.settings.connect(&self.database_name)
+    }
+
+    fn is_valid(&self, connection: &mut Client) -> DatabaseResult<()> {
+        connection
+            .simple_query("")
+            .map(|_| ())
+            .map_err(DatabaseError::query_execution_error)
+    }
+
+    fn has_broken(&self, connection: &mut Client) -> bool {
+        connection.is_closed()
+    }
+}
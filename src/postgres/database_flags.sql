@@ -0,0 +1,5 @@
+select
+  db.datistemplate as is_template,
+  db.datallowconn as allow_connections
+from pg_database as db
+where db.datname = $1
@@ -0,0 +1,114 @@
+use postgres::Row;
+
+#[derive(Debug, Clone)]
+pub struct DatabaseInfo {
+    name: String,
+    modified: i64,
+    size: u64,
+    owner: String,
+    comment: Option<String>,
+    encoding: String,
+    collation: String,
+    ctype: String,
+    tablespace: Option<String>,
+    connection_limit: i32,
+    allow_connections: bool,
+    is_template: bool,
+}
+
+impl DatabaseInfo {
+    /// Create database info from row of `database_list.sql` query.
+    pub fn from_row(row: &Row) -> DatabaseInfo {
+        let size: i64 = row.get(2);
+
+        DatabaseInfo {
+            name: row.get(0),
+            modified: row.get(1),
+            size: size as u64,
+            owner: row.get(3),
+            comment: row.get(4),
+            encoding: row.get(5),
+            collation: row.get(6),
+            ctype: row.get(7),
+            tablespace: row.get(8),
+            connection_limit: row.get(9),
+            allow_connections: row.get(10),
+            is_template: row.get(11),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn new(name: &str, modified: i64, size: u64) -> DatabaseInfo {
+        DatabaseInfo {
+            name: name.into(),
+            modified,
+            size,
+            owner: "postgres".into(),
+            comment: None,
+            encoding: "UTF8".into(),
+            collation: "C".into(),
+            ctype: "C".into(),
+            tablespace: None,
+            connection_limit: -1,
+            allow_connections: true,
+            is_template: false,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_flags(mut self, allow_connections: bool, is_template: bool) -> DatabaseInfo {
+        self.allow_connections = allow_connections;
+        self.is_template = is_template;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn modified(&self) -> i64 {
+        self.modified
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns name of the role which owns the database.
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn encoding(&self) -> &str {
+        &self.encoding
+    }
+
+    pub fn collation(&self) -> &str {
+        &self.collation
+    }
+
+    pub fn ctype(&self) -> &str {
+        &self.ctype
+    }
+
+    pub fn tablespace(&self) -> Option<&str> {
+        self.tablespace.as_deref()
+    }
+
+    /// Returns maximal number of concurrent connections, -1 means no limit.
+    pub fn connection_limit(&self) -> i32 {
+        self.connection_limit
+    }
+
+    pub fn allow_connections(&self) -> bool {
+        self.allow_connections
+    }
+
+    pub fn is_template(&self) -> bool {
+        self.is_template
+    }
+}
@@ -1,5 +1,15 @@
 select
   db.datname as name,
   extract(epoch from (pg_stat_file('base/' || db.oid || '/PG_VERSION')).modification)::bigint as date,
-  pg_database_size(datname) as size
+  pg_database_size(datname) as size,
+  pg_get_userbyid(db.datdba)::text as owner,
+  shobj_description(db.oid, 'pg_database') as comment,
+  pg_encoding_to_char(db.encoding)::text as encoding,
+  db.datcollate::text as collation,
+  db.datctype::text as ctype,
+  ts.spcname::text as tablespace,
+  db.datconnlimit as connection_limit,
+  db.datallowconn as allow_connections,
+  db.datistemplate as is_template
 from pg_database as db
+left join pg_tablespace as ts on ts.oid = db.dattablespace
@@ -0,0 +1,12 @@
+select
+  sa.pid as pid,
+  sa.usename::text as user_name,
+  sa.application_name as application_name,
+  host(sa.client_addr) as client_address,
+  sa.state as state,
+  extract(epoch from sa.backend_start)::bigint as backend_start,
+  extract(epoch from sa.xact_start)::bigint as transaction_start
+from pg_stat_activity as sa
+where sa.datname = $1
+  and sa.pid <> pg_backend_pid()
+  and sa.application_name <> $2
@@ -0,0 +1,58 @@
+use postgres::Row;
+
+/// Cumulative activity counters of the database from `pg_stat_database`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseStats {
+    xact_commit: i64,
+    xact_rollback: i64,
+    tup_returned: i64,
+    tup_fetched: i64,
+    tup_inserted: i64,
+    tup_updated: i64,
+    tup_deleted: i64,
+    blks_read: i64,
+    stats_reset: Option<i64>,
+}
+
+impl DatabaseStats {
+    /// Create statistics from row of `database_stats.sql` query. The first column must contain database name.
+    pub fn from_row(row: &Row) -> DatabaseStats {
+        DatabaseStats {
+            xact_commit: row.get(1),
+            xact_rollback: row.get(2),
+            tup_returned: row.get(3),
+            tup_fetched: row.get(4),
+            tup_inserted: row.get(5),
+            tup_updated: row.get(6),
+            tup_deleted: row.get(7),
+            blks_read: row.get(8),
+            stats_reset: row.get(9),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn new(xact_commit: i64, stats_reset: Option<i64>) -> DatabaseStats {
+        DatabaseStats {
+            xact_commit,
+            stats_reset,
+            ..Default::default()
+        }
+    }
+
+    /// Returns UNIX time when statistics were reset last time.
+    pub fn stats_reset(&self) -> Option<i64> {
+        self.stats_reset
+    }
+
+    /// Returns total number of operations.
+    pub fn operations(&self) -> i64 {
+        self.xact_commit
+            + self.xact_rollback
+            + self.tup_returned
+            + self.tup_fetched
+            + self.tup_inserted
+            + self.tup_updated
+            + self.tup_deleted
+            + self.blks_read
+    }
+}
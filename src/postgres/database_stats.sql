@@ -0,0 +1,13 @@
+select
+  sd.datname::text as name,
+  sd.xact_commit as xact_commit,
+  sd.xact_rollback as xact_rollback,
+  sd.tup_returned as tup_returned,
+  sd.tup_fetched as tup_fetched,
+  sd.tup_inserted as tup_inserted,
+  sd.tup_updated as tup_updated,
+  sd.tup_deleted as tup_deleted,
+  sd.blks_read as blks_read,
+  extract(epoch from sd.stats_reset)::bigint as stats_reset
+from pg_stat_database as sd
+where sd.datname is not null
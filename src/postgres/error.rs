@@ -1,4 +1,7 @@
+use super::Session;
+use postgres::error::SqlState;
 use postgres::Error as PgError;
+use r2d2::Error as PoolError;
 use std::error::Error;
 use std::fmt::Display;
 use std::fmt::Formatter;
@@ -6,26 +9,79 @@ use std::fmt::Result as FmtResult;
 
 pub type DatabaseResult<T> = Result<T, DatabaseError>;
 
+const TLS_ERROR_PREFIX: &str = "error performing TLS handshake";
+const TLS_CONFIGURATION_PREFIX: &str = "TLS configuration error";
+const CONNECT_TIMEOUT_MESSAGE: &str = "connection timed out";
+const POOL_TIMEOUT_MESSAGE: &str = "timed out waiting for connection";
+
 #[derive(Debug)]
 pub enum DatabaseError {
     ConnectionError { message: String },
+    TlsError { message: String },
+    ConnectTimeout { message: String },
+    StatementTimeout { message: String },
     QueryExecutionError { message: String },
+    DropRefused { message: String },
+    ActiveSessions { sessions: Vec<Session> },
 }
 
 impl DatabaseError {
     #[allow(clippy::needless_pass_by_value)]
     pub fn connection_error(error: PgError) -> DatabaseError {
-        DatabaseError::ConnectionError {
-            message: format!("{}", error),
+        // Kind of the error is not public, TLS errors and timeouts are recognized by description.
+        DatabaseError::from_connection_message(format!("{}", error))
+    }
+
+    /// Create error from failure to take connection from pool. Pool reports only description of the last connection
+    /// error, so TLS errors and timeouts are recognized by description too. If pool did not get any error while it
+    /// was waiting for connection, it is considered as connect timeout.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn pool_error(error: PoolError) -> DatabaseError {
+        let message = format!("{}", error);
+
+        if message == POOL_TIMEOUT_MESSAGE {
+            DatabaseError::ConnectTimeout { message }
+        } else {
+            DatabaseError::from_connection_message(message)
+        }
+    }
+
+    fn from_connection_message(message: String) -> DatabaseError {
+        if message.contains(TLS_ERROR_PREFIX) || message.contains(TLS_CONFIGURATION_PREFIX) {
+            DatabaseError::TlsError { message }
+        } else if message.contains(CONNECT_TIMEOUT_MESSAGE) {
+            DatabaseError::ConnectTimeout { message }
+        } else {
+            DatabaseError::ConnectionError { message }
+        }
+    }
+
+    pub fn tls_error(message: &str) -> DatabaseError {
+        DatabaseError::TlsError {
+            message: format!("{}: {}", TLS_CONFIGURATION_PREFIX, message),
         }
     }
 
     #[allow(clippy::needless_pass_by_value)]
     pub fn query_execution_error(error: PgError) -> DatabaseError {
-        DatabaseError::QueryExecutionError {
-            message: format!("{}", error),
+        let message = format!("{}", error);
+
+        if error.code() == Some(&SqlState::QUERY_CANCELED) {
+            DatabaseError::StatementTimeout { message }
+        } else {
+            DatabaseError::QueryExecutionError { message }
         }
     }
+
+    pub fn drop_refused(message: &str) -> DatabaseError {
+        DatabaseError::DropRefused {
+            message: message.into(),
+        }
+    }
+
+    pub fn active_sessions(sessions: Vec<Session>) -> DatabaseError {
+        DatabaseError::ActiveSessions { sessions }
+    }
 }
 
 impl Error for DatabaseError {}
@@ -34,7 +90,58 @@ impl Display for DatabaseError {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         match self {
             DatabaseError::ConnectionError { message } => write!(f, "{}", message),
+            DatabaseError::TlsError { message } => write!(f, "{}", message),
+            DatabaseError::ConnectTimeout { message } => write!(f, "{}", message),
+            DatabaseError::StatementTimeout { message } => write!(f, "{}", message),
             DatabaseError::QueryExecutionError { message } => write!(f, "{}", message),
+            DatabaseError::DropRefused { message } => write!(f, "{}", message),
+            DatabaseError::ActiveSessions { sessions } => {
+                write!(f, "database has {} active sessions", sessions.len())
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_message_of_tls_handshake_is_tls_error() {
+        let error = DatabaseError::from_connection_message(
+            "error performing TLS handshake: certificate verify failed".into(),
+        );
+
+        assert!(matches!(error, DatabaseError::TlsError { .. }));
+    }
+
+    #[test]
+    fn tls_configuration_error_is_recognized_after_pool() {
+        let message = format!("{}", DatabaseError::tls_error("invalid root certificate"));
+        let error = DatabaseError::from_connection_message(message);
+
+        assert!(matches!(error, DatabaseError::TlsError { .. }));
+    }
+
+    #[test]
+    fn connection_message_of_timeout_is_connect_timeout() {
+        let error = DatabaseError::from_connection_message(
+            "error connecting to server: connection timed out".into(),
+        );
+
+        assert!(matches!(error, DatabaseError::ConnectTimeout { .. }));
+    }
+
+    #[test]
+    fn other_connection_message_is_connection_error() {
+        let error = DatabaseError::from_connection_message(
+            "error connecting to server: Connection refused (os error 111)".into(),
+        );
+
+        assert!(matches!(error, DatabaseError::ConnectionError { .. }));
+        assert_eq!(
+            format!("{}", error),
+            "error connecting to server: Connection refused (os error 111)"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+select
+  schema_name,
+  table_name,
+  index_name,
+  (bs * relpages)::bigint as size,
+  case when relpages > estimated_pages
+    then (bs * (relpages - estimated_pages))::bigint
+    else 0::bigint
+  end as wasted
+from (
+  select
+    coalesce(1 + ceil(reltuples / floor((bs - page_opaque - page_header) * fillfactor / (100 * (4 + tuple_width)::float))), 0)
+      as estimated_pages,
+    bs,
+    schema_name,
+    table_name,
+    index_name,
+    relpages,
+    is_na
+  from (
+    select
+      bs,
+      schema_name,
+      table_name,
+      index_name,
+      reltuples,
+      relpages,
+      fillfactor,
+      (tuple_header + max_align - case when tuple_header % max_align = 0 then max_align else tuple_header % max_align end
+        + data_width + max_align - case
+          when data_width = 0 then 0
+          when data_width::integer % max_align = 0 then max_align
+          else data_width::integer % max_align
+        end)::numeric as tuple_width,
+      page_header,
+      page_opaque,
+      is_na
+    from (
+      select
+        n.nspname::text as schema_name,
+        i.table_name,
+        i.index_name,
+        i.reltuples,
+        i.relpages,
+        i.fillfactor,
+        current_setting('block_size')::numeric as bs,
+        case when version() ~ 'mingw32' or version() ~ '64-bit|x86_64|ppc64|ia64|amd64' then 8 else 4 end as max_align,
+        24 as page_header,
+        16 as page_opaque,
+        case when max(coalesce(s.null_frac, 0)) = 0 then 8 else 8 + ((32 + 8 - 1) / 8) end as tuple_header,
+        sum((1 - coalesce(s.null_frac, 0)) * coalesce(s.avg_width, 1024)) as data_width,
+        max(case when i.atttypid = 'pg_catalog.name'::regtype then 1 else 0 end) > 0 as is_na
+      from (
+        select
+          ct.relname::text as table_name,
+          ct.relnamespace,
+          ic.index_name,
+          ic.reltuples,
+          ic.relpages,
+          ic.fillfactor,
+          coalesce(a1.attname, a2.attname) as attname,
+          coalesce(a1.atttypid, a2.atttypid) as atttypid,
+          case when a1.attnum is null then ic.index_name else ct.relname::text end as stats_relation
+        from (
+          select
+            ci.relname::text as index_name,
+            ci.reltuples,
+            ci.relpages,
+            i.indrelid as table_oid,
+            i.indexrelid as index_oid,
+            coalesce(substring(array_to_string(ci.reloptions, ' ') from 'fillfactor=([0-9]+)')::smallint, 90) as fillfactor,
+            string_to_array(textin(int2vectorout(i.indkey)), ' ')::int[] as indkey,
+            generate_series(1, i.indnatts) as position
+          from pg_index as i
+            join pg_class as ci on ci.oid = i.indexrelid
+          where ci.relam = (select oid from pg_am where amname = 'btree')
+            and ci.relpages > 0
+        ) as ic
+          join pg_class as ct on ct.oid = ic.table_oid
+          left join pg_attribute as a1 on ic.indkey[ic.position] <> 0
+            and a1.attrelid = ic.table_oid
+            and a1.attnum = ic.indkey[ic.position]
+          left join pg_attribute as a2 on ic.indkey[ic.position] = 0
+            and a2.attrelid = ic.index_oid
+            and a2.attnum = ic.position
+      ) as i
+        join pg_namespace as n on n.oid = i.relnamespace
+        join pg_stats as s on s.schemaname = n.nspname
+          and s.tablename = i.stats_relation
+          and s.attname = i.attname
+      where n.nspname not in ('pg_catalog', 'information_schema')
+      group by 1, 2, 3, 4, 5, 6
+    ) as index_stats
+  ) as index_tuples
+) as index_pages
+where not is_na
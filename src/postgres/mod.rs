@@ -1,33 +1,128 @@
+mod bloat;
+mod connection;
+mod database_info;
+mod database_stats;
 mod error;
+mod own_queries;
+mod session;
+mod session_stats;
+mod table_size;
 
+pub use self::bloat::Bloat;
+pub use self::bloat::BloatMethod;
+use self::connection::ConnectionManager;
+use self::connection::ConnectionSettings;
+pub use self::connection::APPLICATION_NAME;
+pub use self::database_info::DatabaseInfo;
+pub use self::database_stats::DatabaseStats;
 pub use self::error::DatabaseError;
 pub use self::error::DatabaseResult;
-use postgres::config::SslMode;
+use self::own_queries::OwnQueries;
+pub use self::session::Session;
+pub use self::session_stats::SessionStats;
+pub use self::table_size::TableSize;
+use crate::config::PoolConfig;
+use crate::config::ServerConfig;
 use postgres::Client;
-use postgres::NoTls;
+use r2d2::NopErrorHandler;
+use r2d2::Pool;
+use r2d2::PooledConnection;
+use scheduled_thread_pool::ScheduledThreadPool;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::time::Duration;
+use std::time::Instant;
 
+const TEMPLATE_DATABASES: [&str; 2] = ["template0", "template1"];
+const POOL_THREADS: usize = 3;
+const MAINTENANCE_DATABASE: &str = "postgres";
+
+/// Shared PostgreSQL instances of configured servers.
+#[derive(Debug, Clone)]
+pub struct PostgresRef {
+    servers: Arc<Mutex<HashMap<String, Arc<PostgreSQL>>>>,
+    thread_pool: ThreadPool,
+}
+
+impl PostgresRef {
+    /// Returns PostgreSQL instance of the server. Instance will be created on first use and recreated when connection
+    /// settings of the server are changed by configuration reload.
+    pub fn server(&self, server_config: &ServerConfig) -> Arc<PostgreSQL> {
+        let mut servers = self.servers.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match servers.get(server_config.name()) {
+            Some(postgres) if postgres.matches(server_config) => postgres.clone(),
+            _ => {
+                let postgres = Arc::new(PostgreSQL::new(server_config, self.thread_pool.clone()));
+                servers.insert(server_config.name().into(), postgres.clone());
+
+                postgres
+            }
+        }
+    }
+}
+
+/// Connection to the server with pools of connections to its databases. Pools are kept only for databases returned by
+/// the last database list which allow connections and are not templates, other databases are connected on every use.
 #[derive(Debug)]
 pub struct PostgreSQL {
-    server: String,
-    port: u16,
-    user: String,
-    password: String,
+    settings: Arc<ConnectionSettings>,
+    pool_config: PoolConfig,
+    thread_pool: ThreadPool,
+    pools: Mutex<Pools>,
+    own_queries: Arc<Mutex<OwnQueries>>,
+}
+
+/// Threads which maintain connections of all pools, so number of threads does not grow with number of databases.
+#[derive(Clone)]
+struct ThreadPool(Arc<ScheduledThreadPool>);
+
+impl Debug for ThreadPool {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str("ThreadPool")
+    }
+}
+
+#[derive(Debug, Default)]
+struct Pools {
+    poolable: HashSet<String>,
+    pools: HashMap<String, Pool<ConnectionManager>>,
 }
 
 impl PostgreSQL {
-    pub fn new(server: &str, port: u16, user: &str, password: &str) -> PostgreSQL {
+    fn new(server_config: &ServerConfig, thread_pool: ThreadPool) -> PostgreSQL {
+        let mut pools = Pools::default();
+
+        // Connections to the maintenance database are used for every server query.
+        pools.poolable.insert(MAINTENANCE_DATABASE.into());
+
         PostgreSQL {
-            server: server.into(),
-            port,
-            user: user.into(),
-            password: password.into(),
+            settings: Arc::new(ConnectionSettings::new(server_config)),
+            pool_config: server_config.pool().clone(),
+            thread_pool,
+            pools: Mutex::new(pools),
+            own_queries: Arc::new(Mutex::new(OwnQueries::default())),
         }
     }
 
-    pub fn database_list<F, T>(&self, callback: F) -> DatabaseResult<Vec<T>>
-    where
-        F: Fn(&str, i64, u64) -> T,
-    {
+    /// Returns `true` if instance was created with the same connection and pool settings.
+    fn matches(&self, server_config: &ServerConfig) -> bool {
+        *self.settings == ConnectionSettings::new(server_config)
+            && self.pool_config == *server_config.pool()
+    }
+
+    /// Returns all databases of the server. Pools of databases which disappeared or can not be pooled are closed.
+    pub fn database_list(&self) -> DatabaseResult<Vec<DatabaseInfo>> {
         let mut connection = self.connect()?;
         let mut result = Vec::new();
 
@@ -35,19 +130,187 @@ impl PostgreSQL {
             .query(include_str!("database_list.sql"), &[])
             .map_err(DatabaseError::query_execution_error)?
             .iter()
+        {
+            result.push(DatabaseInfo::from_row(row));
+        }
+
+        let mut pools = self.lock_pools();
+
+        pools.poolable = result
+            .iter()
+            .filter(|info| info.allow_connections() && !info.is_template())
+            .map(|info| info.name().to_string())
+            .chain(Some(MAINTENANCE_DATABASE.to_string()))
+            .collect();
+
+        let Pools { poolable, pools } = &mut *pools;
+
+        pools.retain(|name, _| poolable.contains(name));
+
+        Ok(result)
+    }
+
+    /// Returns activity counters of every database.
+    pub fn database_stats(&self) -> DatabaseResult<HashMap<String, DatabaseStats>> {
+        let mut connection = self.connect()?;
+        let mut result = HashMap::new();
+
+        for row in connection
+            .query(include_str!("database_stats.sql"), &[])
+            .map_err(DatabaseError::query_execution_error)?
+            .iter()
+        {
+            let name: String = row.get(0);
+
+            result.insert(name, DatabaseStats::from_row(row));
+        }
+
+        Ok(result)
+    }
+
+    /// Returns databases whose activity counters could be changed by queries of the application since the previous
+    /// call. It must be called after every query of `database_stats`.
+    pub fn queried_databases(&self) -> HashSet<String> {
+        lock_own_queries(&self.own_queries).sample(Instant::now())
+    }
+
+    /// Returns session statistics for every database which has sessions.
+    pub fn session_stats(&self) -> DatabaseResult<HashMap<String, SessionStats>> {
+        let mut connection = self.connect()?;
+        let mut result = HashMap::new();
+
+        for row in connection
+            .query(include_str!("session_stats.sql"), &[&APPLICATION_NAME])
+            .map_err(DatabaseError::query_execution_error)?
+            .iter()
         {
             let name: String = row.get(0);
-            let modified: i64 = row.get(1);
-            let size: i64 = row.get(2);
 
-            result.push(callback(&name, modified, size as u64));
+            result.insert(name, SessionStats::from_row(row));
+        }
+
+        Ok(result)
+    }
+
+    pub fn sessions(&self, database_name: &str) -> DatabaseResult<Vec<Session>> {
+        let mut connection = self.connect()?;
+
+        query_sessions(&mut connection, database_name)
+    }
+
+    /// Returns sizes of user tables of the database sorted by total size.
+    pub fn table_sizes(&self, database_name: &str) -> DatabaseResult<Vec<TableSize>> {
+        let mut connection = self.connect_to(database_name)?;
+        let mut result = Vec::new();
+
+        for row in connection
+            .query(include_str!("table_sizes.sql"), &[])
+            .map_err(DatabaseError::query_execution_error)?
+            .iter()
+        {
+            result.push(TableSize::from_row(row));
+        }
+
+        Ok(result)
+    }
+
+    /// Returns estimated bloat of tables and B-tree indexes of the database sorted by wasted bytes. If `pgstattuple` is
+    /// set and the extension is installed in the database, bloat of tables will be measured with `pgstattuple_approx`.
+    pub fn bloat(&self, database_name: &str, pgstattuple: bool) -> DatabaseResult<Vec<Bloat>> {
+        let mut connection = self.connect_to(database_name)?;
+        let mut result = Vec::new();
+        let use_pgstattuple = pgstattuple && {
+            let row = connection
+                .query_one(include_str!("extension_exists.sql"), &[&"pgstattuple"])
+                .map_err(DatabaseError::query_execution_error)?;
+
+            row.get::<_, bool>(0)
+        };
+        let (table_query, table_method) = if use_pgstattuple {
+            (
+                include_str!("table_bloat_pgstattuple.sql"),
+                BloatMethod::Pgstattuple,
+            )
+        } else {
+            (include_str!("table_bloat.sql"), BloatMethod::Estimate)
+        };
+
+        for (query, method) in &[
+            (table_query, table_method),
+            (include_str!("index_bloat.sql"), BloatMethod::Estimate),
+        ] {
+            for row in connection
+                .query(*query, &[])
+                .map_err(DatabaseError::query_execution_error)?
+                .iter()
+            {
+                result.push(Bloat::from_row(row, *method));
+            }
         }
 
+        result.sort_by_key(|bloat| Reverse(bloat.wasted()));
+
         Ok(result)
     }
 
-    pub fn drop_database(&self, database_name: &str) -> DatabaseResult<()> {
+    /// Drop database terminating all its sessions. Service databases, templates and databases which do not allow
+    /// connections will not be dropped. If `require_force` is set and database has active sessions, database will be
+    /// dropped only if `force` is set.
+    pub fn drop_database(
+        &self,
+        database_name: &str,
+        service_databases: &HashSet<String>,
+        require_force: bool,
+        force: bool,
+    ) -> DatabaseResult<()> {
+        if service_databases.contains(database_name) {
+            return Err(DatabaseError::drop_refused(&format!(
+                "database `{}` is service database",
+                database_name
+            )));
+        }
+
+        if TEMPLATE_DATABASES.contains(&database_name) {
+            return Err(DatabaseError::drop_refused(&format!(
+                "database `{}` is template database",
+                database_name
+            )));
+        }
+
         let mut connection = self.connect()?;
+        let rows = connection
+            .query(include_str!("database_flags.sql"), &[&database_name])
+            .map_err(DatabaseError::query_execution_error)?;
+        let row = rows.first().ok_or_else(|| {
+            DatabaseError::drop_refused(&format!("database `{}` does not exist", database_name))
+        })?;
+        let is_template: bool = row.get(0);
+        let allow_connections: bool = row.get(1);
+
+        if is_template {
+            return Err(DatabaseError::drop_refused(&format!(
+                "database `{}` is template database",
+                database_name
+            )));
+        }
+
+        if !allow_connections {
+            return Err(DatabaseError::drop_refused(&format!(
+                "database `{}` does not allow connections",
+                database_name
+            )));
+        }
+
+        // Idle pooled connections must not be reported as sessions of the database.
+        self.remove_pool(database_name);
+
+        if require_force && !force {
+            let sessions = query_sessions(&mut connection, database_name)?;
+
+            if !sessions.is_empty() {
+                return Err(DatabaseError::active_sessions(sessions));
+            }
+        }
 
         connection
             .execute(include_str!("teminate_backends.sql"), &[&database_name])
@@ -63,15 +326,133 @@ impl PostgreSQL {
         Ok(())
     }
 
-    fn connect(&self) -> DatabaseResult<Client> {
-        Client::configure()
-            .ssl_mode(SslMode::Disable)
-            .host(&self.server)
-            .port(self.port)
-            .user(&self.user)
-            .user(&self.password)
-            .dbname("postgres")
-            .connect(NoTls)
-            .map_err(DatabaseError::connection_error)
+    fn connect(&self) -> DatabaseResult<Connection> {
+        self.connect_to(MAINTENANCE_DATABASE)
+    }
+
+    /// Take connection to the database from its pool. Pool will be created on first use. Databases which can not be
+    /// pooled get new connection which is closed after use, so no idle connections are kept to templates.
+    fn connect_to(&self, database_name: &str) -> DatabaseResult<Connection> {
+        lock_own_queries(&self.own_queries).record(database_name, Instant::now());
+
+        let client = match self.pool(database_name) {
+            Some(pool) => pool
+                .get()
+                .map(ConnectionClient::Pooled)
+                .map_err(DatabaseError::pool_error)?,
+            None => ConnectionClient::Direct(self.settings.connect(database_name)?),
+        };
+
+        Ok(Connection {
+            client,
+            database_name: database_name.into(),
+            own_queries: self.own_queries.clone(),
+        })
+    }
+
+    fn pool(&self, database_name: &str) -> Option<Pool<ConnectionManager>> {
+        let mut pools = self.lock_pools();
+
+        if !pools.poolable.contains(database_name) {
+            return None;
+        }
+
+        let pool = pools
+            .pools
+            .entry(database_name.into())
+            .or_insert_with(|| {
+                Pool::builder()
+                    .max_size(self.pool_config.size())
+                    .min_idle(Some(0))
+                    .idle_timeout(Some(Duration::from_secs(self.pool_config.idle_timeout())))
+                    .test_on_check_out(self.pool_config.health_check())
+                    .connection_timeout(self.settings.connect_timeout())
+                    .error_handler(Box::new(NopErrorHandler))
+                    .thread_pool(self.thread_pool.0.clone())
+                    .build_unchecked(ConnectionManager::new(self.settings.clone(), database_name))
+            })
+            .clone();
+
+        Some(pool)
+    }
+
+    /// Close idle connections to the database and forget its pool.
+    fn remove_pool(&self, database_name: &str) {
+        self.lock_pools().pools.remove(database_name);
+    }
+
+    fn lock_pools(&self) -> MutexGuard<'_, Pools> {
+        self.pools.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Connection taken from pool or created for one use. Release of connection is recorded as own query of its database.
+struct Connection {
+    client: ConnectionClient,
+    database_name: String,
+    own_queries: Arc<Mutex<OwnQueries>>,
+}
+
+enum ConnectionClient {
+    Pooled(PooledConnection<ConnectionManager>),
+    Direct(Client),
+}
+
+impl Deref for Connection {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        match &self.client {
+            ConnectionClient::Pooled(connection) => connection,
+            ConnectionClient::Direct(connection) => connection,
+        }
+    }
+}
+
+impl DerefMut for Connection {
+    fn deref_mut(&mut self) -> &mut Client {
+        match &mut self.client {
+            ConnectionClient::Pooled(connection) => connection,
+            ConnectionClient::Direct(connection) => connection,
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        lock_own_queries(&self.own_queries).record(&self.database_name, Instant::now());
+    }
+}
+
+fn lock_own_queries(own_queries: &Mutex<OwnQueries>) -> MutexGuard<'_, OwnQueries> {
+    own_queries.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn query_sessions(connection: &mut Client, database_name: &str) -> DatabaseResult<Vec<Session>> {
+    let mut result = Vec::new();
+
+    for row in connection
+        .query(
+            include_str!("database_sessions.sql"),
+            &[&database_name, &APPLICATION_NAME],
+        )
+        .map_err(DatabaseError::query_execution_error)?
+        .iter()
+    {
+        result.push(Session::from_row(row));
+    }
+
+    Ok(result)
+}
+
+pub fn create() -> PostgresRef {
+    PostgresRef {
+        servers: Arc::new(Mutex::new(HashMap::new())),
+        thread_pool: ThreadPool(Arc::new(
+            ScheduledThreadPool::builder()
+                .num_threads(POOL_THREADS)
+                .thread_name_pattern("r2d2-worker-{}")
+                .build(),
+        )),
     }
 }
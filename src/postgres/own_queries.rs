@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Maximal delay of statistics updates by the server. Backends report their counters after transaction, idle
+/// backends may postpone reports for several seconds and busy backends report them at least once a minute.
+const STATISTICS_DELAY: Duration = Duration::from_secs(60);
+
+/// Times of the last queries of the application to every database. Own queries change activity counters of the
+/// database, so such changes must not be reported as its activity.
+#[derive(Debug, Default)]
+pub struct OwnQueries {
+    queried: HashMap<String, Instant>,
+    sampled: Option<Instant>,
+}
+
+impl OwnQueries {
+    pub fn record(&mut self, database_name: &str, now: Instant) {
+        self.queried.insert(database_name.into(), now);
+    }
+
+    /// Returns databases whose counters may be changed by own queries since the previous sample of statistics. Own
+    /// queries made shortly before the previous sample are included, because the server reports statistics with delay.
+    pub fn sample(&mut self, now: Instant) -> HashSet<String> {
+        let since = self
+            .sampled
+            .and_then(|sampled| sampled.checked_sub(STATISTICS_DELAY));
+        let result = self
+            .queried
+            .iter()
+            .filter(|(_, queried)| since.is_none_or(|since| **queried >= since))
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        // Queries older than statistics delay are not included in the next sample.
+        if let Some(expired) = now.checked_sub(STATISTICS_DELAY) {
+            self.queried.retain(|_, queried| *queried >= expired);
+        }
+
+        self.sampled = Some(now);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_sample_contains_all_queried_databases() {
+        let now = Instant::now();
+        let mut own_queries = OwnQueries::default();
+        own_queries.record("app", now);
+
+        assert!(own_queries.sample(now).contains("app"));
+    }
+
+    #[test]
+    fn sample_contains_databases_queried_before_previous_sample_within_delay() {
+        let start = Instant::now();
+        let mut own_queries = OwnQueries::default();
+        own_queries.sample(start);
+        own_queries.record("app", start);
+
+        let second = start + Duration::from_secs(30);
+        assert!(own_queries.sample(second).contains("app"));
+
+        let third = second + Duration::from_secs(30);
+        assert!(own_queries.sample(third).contains("app"));
+    }
+
+    #[test]
+    fn sample_excludes_databases_queried_long_before_previous_sample() {
+        let start = Instant::now();
+        let mut own_queries = OwnQueries::default();
+        own_queries.sample(start);
+        own_queries.record("app", start);
+
+        let second = start + STATISTICS_DELAY * 2;
+        own_queries.sample(second);
+
+        let third = second + Duration::from_secs(30);
+        assert!(own_queries.sample(third).is_empty());
+    }
+}
@@ -0,0 +1,35 @@
+use postgres::Row;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    pid: i32,
+    user: Option<String>,
+    application: Option<String>,
+    client_address: Option<String>,
+    state: Option<String>,
+    backend_start: Option<i64>,
+    transaction_start: Option<i64>,
+}
+
+impl Session {
+    /// Create session from row of `database_sessions.sql` query.
+    pub fn from_row(row: &Row) -> Session {
+        Session {
+            pid: row.get(0),
+            user: row.get(1),
+            application: row.get(2),
+            client_address: row.get(3),
+            state: row.get(4),
+            backend_start: row.get(5),
+            transaction_start: row.get(6),
+        }
+    }
+
+    /// Returns session with hidden client address.
+    pub fn without_client_address(self) -> Session {
+        Session {
+            client_address: None,
+            ..self
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use postgres::Row;
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct SessionStats {
+    active: u64,
+    idle: u64,
+    idle_in_transaction: u64,
+    oldest_transaction_age: Option<i64>,
+    client_addresses: Vec<String>,
+    applications: Vec<String>,
+}
+
+impl SessionStats {
+    /// Create session statistics from row of `session_stats.sql` query. The first column must contain database name.
+    pub fn from_row(row: &Row) -> SessionStats {
+        let active: i64 = row.get(1);
+        let idle: i64 = row.get(2);
+        let idle_in_transaction: i64 = row.get(3);
+
+        SessionStats {
+            active: active as u64,
+            idle: idle as u64,
+            idle_in_transaction: idle_in_transaction as u64,
+            oldest_transaction_age: row.get(4),
+            client_addresses: row.get(5),
+            applications: row.get(6),
+        }
+    }
+
+    /// Returns statistics with hidden client addresses.
+    pub fn without_client_addresses(self) -> SessionStats {
+        SessionStats {
+            client_addresses: Vec::new(),
+            ..self
+        }
+    }
+
+    /// Returns `true` if database has sessions in any known state.
+    pub fn has_sessions(&self) -> bool {
+        self.active + self.idle + self.idle_in_transaction > 0
+    }
+
+    #[cfg(test)]
+    pub fn idle(idle: u64) -> SessionStats {
+        SessionStats {
+            idle,
+            ..SessionStats::default()
+        }
+    }
+}
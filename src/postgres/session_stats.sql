@@ -0,0 +1,13 @@
+select
+  sa.datname::text as name,
+  count(*) filter (where sa.state = 'active') as active,
+  count(*) filter (where sa.state = 'idle') as idle,
+  count(*) filter (where sa.state like 'idle in transaction%') as idle_in_transaction,
+  extract(epoch from now() - min(sa.xact_start))::bigint as oldest_transaction_age,
+  array_remove(array_agg(distinct host(sa.client_addr)), null) as client_addresses,
+  array_remove(array_agg(distinct sa.application_name), '') as applications
+from pg_stat_activity as sa
+where sa.datname is not null
+  and sa.pid <> pg_backend_pid()
+  and sa.application_name <> $1
+group by sa.datname
@@ -0,0 +1,67 @@
+select
+  schema_name,
+  table_name,
+  null::text as index_name,
+  (bs * table_pages)::bigint as size,
+  case when table_pages - estimated_pages > 0
+    then ((table_pages - estimated_pages) * bs)::bigint
+    else 0::bigint
+  end as wasted
+from (
+  select
+    ceil(reltuples / ((bs - page_header) * fillfactor / (tuple_size * 100))) + ceil(toast_tuples / 4) as estimated_pages,
+    heap_pages + toast_pages as table_pages,
+    bs,
+    schema_name,
+    table_name,
+    is_na
+  from (
+    select
+      4 + tuple_header_size + tuple_data_size + (2 * ma)
+        - case when tuple_header_size % ma = 0 then ma else tuple_header_size % ma end
+        - case when ceil(tuple_data_size)::int % ma = 0 then ma else ceil(tuple_data_size)::int % ma end
+        as tuple_size,
+      heap_pages,
+      toast_pages,
+      reltuples,
+      toast_tuples,
+      bs,
+      page_header,
+      schema_name,
+      table_name,
+      fillfactor,
+      is_na
+    from (
+      select
+        ns.nspname::text as schema_name,
+        tbl.relname::text as table_name,
+        tbl.reltuples,
+        tbl.relpages as heap_pages,
+        coalesce(toast.relpages, 0) as toast_pages,
+        coalesce(toast.reltuples, 0) as toast_tuples,
+        coalesce(substring(array_to_string(tbl.reloptions, ' ') from 'fillfactor=([0-9]+)')::smallint, 100) as fillfactor,
+        current_setting('block_size')::numeric as bs,
+        case when version() ~ 'mingw32' or version() ~ '64-bit|x86_64|ppc64|ia64|amd64' then 8 else 4 end as ma,
+        24 as page_header,
+        23 + case when max(coalesce(s.null_frac, 0)) > 0 then (7 + count(s.attname)) / 8 else 0::int end
+          as tuple_header_size,
+        sum((1 - coalesce(s.null_frac, 0)) * coalesce(s.avg_width, 0)) as tuple_data_size,
+        bool_or(att.atttypid = 'pg_catalog.name'::regtype)
+          or sum(case when att.attnum > 0 then 1 else 0 end) <> count(s.attname) as is_na
+      from pg_attribute as att
+        join pg_class as tbl on att.attrelid = tbl.oid
+        join pg_namespace as ns on ns.oid = tbl.relnamespace
+        left join pg_stats as s on s.schemaname = ns.nspname
+          and s.tablename = tbl.relname
+          and s.inherited = false
+          and s.attname = att.attname
+        left join pg_class as toast on tbl.reltoastrelid = toast.oid
+      where not att.attisdropped
+        and att.attnum > 0
+        and tbl.relkind in ('r', 'm')
+        and ns.nspname not in ('pg_catalog', 'information_schema')
+      group by 1, 2, 3, 4, 5, 6, 7, 8, 9, 10
+    ) as table_stats
+  ) as table_tuples
+) as table_pages
+where not is_na
@@ -0,0 +1,11 @@
+select
+  ns.nspname::text as schema_name,
+  tbl.relname::text as table_name,
+  null::text as index_name,
+  st.table_len as size,
+  (st.dead_tuple_len + st.approx_free_space)::bigint as wasted
+from pg_class as tbl
+  join pg_namespace as ns on ns.oid = tbl.relnamespace
+  cross join lateral pgstattuple_approx(tbl.oid) as st
+where tbl.relkind in ('r', 'm')
+  and ns.nspname not in ('pg_catalog', 'information_schema')
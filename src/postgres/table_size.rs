@@ -0,0 +1,41 @@
+use postgres::Row;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TableSize {
+    schema: String,
+    name: String,
+    total_size: u64,
+    table_size: u64,
+    indexes_size: u64,
+    toast_size: u64,
+    row_estimate: u64,
+    last_vacuum: Option<i64>,
+    last_autovacuum: Option<i64>,
+    last_analyze: Option<i64>,
+    last_autoanalyze: Option<i64>,
+}
+
+impl TableSize {
+    /// Create table size from row of `table_sizes.sql` query.
+    pub fn from_row(row: &Row) -> TableSize {
+        let total_size: i64 = row.get(2);
+        let table_size: i64 = row.get(3);
+        let indexes_size: i64 = row.get(4);
+        let toast_size: i64 = row.get(5);
+        let row_estimate: i64 = row.get(6);
+
+        TableSize {
+            schema: row.get(0),
+            name: row.get(1),
+            total_size: total_size as u64,
+            table_size: table_size as u64,
+            indexes_size: indexes_size as u64,
+            toast_size: toast_size as u64,
+            row_estimate: row_estimate as u64,
+            last_vacuum: row.get(7),
+            last_autovacuum: row.get(8),
+            last_analyze: row.get(9),
+            last_autoanalyze: row.get(10),
+        }
+    }
+}
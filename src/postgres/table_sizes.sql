@@ -0,0 +1,15 @@
+select
+  st.schemaname::text as schema_name,
+  st.relname::text as table_name,
+  pg_total_relation_size(st.relid) as total_size,
+  pg_relation_size(st.relid) as table_size,
+  pg_indexes_size(st.relid) as indexes_size,
+  coalesce(pg_total_relation_size(nullif(c.reltoastrelid, 0)), 0) as toast_size,
+  greatest(c.reltuples, 0)::bigint as row_estimate,
+  extract(epoch from st.last_vacuum)::bigint as last_vacuum,
+  extract(epoch from st.last_autovacuum)::bigint as last_autovacuum,
+  extract(epoch from st.last_analyze)::bigint as last_analyze,
+  extract(epoch from st.last_autoanalyze)::bigint as last_autoanalyze
+from pg_stat_user_tables as st
+  join pg_class as c on c.oid = st.relid
+order by total_size desc, schema_name, table_name
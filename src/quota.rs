@@ -0,0 +1,128 @@
+use crate::config::Config;
+use crate::config::QuotaConfig;
+use crate::state::StateRef;
+use crate::state::StateResult;
+use crate::state::UserUsage;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaStatus {
+    Ok,
+    Soft,
+    Hard,
+}
+
+/// Returns quota status of the user. Quota is exceeded if size or number of databases is greater than hard limit.
+pub fn status(quota: Option<&QuotaConfig>, usage: &UserUsage) -> QuotaStatus {
+    let quota = match quota {
+        Some(quota) => quota,
+        None => return QuotaStatus::Ok,
+    };
+
+    if quota.size().is_some_and(|size| usage.size() > size)
+        || quota
+            .databases()
+            .is_some_and(|databases| usage.databases() > databases)
+    {
+        QuotaStatus::Hard
+    } else if quota
+        .soft_size()
+        .is_some_and(|soft_size| usage.size() >= soft_size)
+    {
+        QuotaStatus::Soft
+    } else {
+        QuotaStatus::Ok
+    }
+}
+
+/// Returns logins of all users who exceeded their quota.
+pub fn over_quota_users(config: &Config, state: &StateRef) -> StateResult<HashSet<String>> {
+    let usages = state.user_usage()?;
+    let mut result = HashSet::new();
+
+    for user in config.users() {
+        let login = user.login().to_lowercase();
+
+        if let Some(usage) = usages.get(&login) {
+            if status(user.quota(), usage) == QuotaStatus::Hard {
+                result.insert(login);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quota(yaml: &str) -> QuotaConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn status_without_quota_is_ok() {
+        assert_eq!(
+            status(None, &UserUsage::new(u64::MAX, 1000)),
+            QuotaStatus::Ok
+        );
+        assert_eq!(
+            status(Some(&quota("{}")), &UserUsage::new(u64::MAX, 1000)),
+            QuotaStatus::Ok
+        );
+    }
+
+    #[test]
+    fn status_is_hard_when_size_exceeds_limit() {
+        let quota = quota("size: 100\nsoft_size: 80");
+
+        assert_eq!(
+            status(Some(&quota), &UserUsage::new(100, 1)),
+            QuotaStatus::Soft
+        );
+        assert_eq!(
+            status(Some(&quota), &UserUsage::new(101, 1)),
+            QuotaStatus::Hard
+        );
+    }
+
+    #[test]
+    fn status_is_hard_when_number_of_databases_exceeds_limit() {
+        let quota = quota("databases: 2");
+
+        assert_eq!(
+            status(Some(&quota), &UserUsage::new(10, 2)),
+            QuotaStatus::Ok
+        );
+        assert_eq!(
+            status(Some(&quota), &UserUsage::new(10, 3)),
+            QuotaStatus::Hard
+        );
+    }
+
+    #[test]
+    fn status_is_soft_from_soft_size() {
+        let quota = quota("soft_size: 80");
+
+        assert_eq!(
+            status(Some(&quota), &UserUsage::new(79, 1)),
+            QuotaStatus::Ok
+        );
+        assert_eq!(
+            status(Some(&quota), &UserUsage::new(80, 1)),
+            QuotaStatus::Soft
+        );
+    }
+
+    #[test]
+    fn status_prefers_hard_over_soft() {
+        let quota = quota("soft_size: 80\ndatabases: 1");
+
+        assert_eq!(
+            status(Some(&quota), &UserUsage::new(90, 2)),
+            QuotaStatus::Hard
+        );
+    }
+}
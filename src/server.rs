@@ -1,55 +1,168 @@
-use crate::config::ConfigRef;
-use crate::config::Cors;
+use crate::audit::AuditRef;
+use crate::auth::AuthMiddleware;
+use crate::config::HttpsConfig;
+use crate::config::SharedConfig;
+use crate::cors::CorsMiddleware;
+use crate::handler::AuditHandler;
+use crate::handler::BloatHandler;
 use crate::handler::DropDbHandler;
+use crate::handler::ExportHandler;
+use crate::handler::HealthHandler;
+use crate::handler::HistoryHandler;
+use crate::handler::HttpsRedirectHandler;
+use crate::handler::MetricsHandler;
+use crate::handler::ReloadHandler;
+use crate::handler::RetentionPlanHandler;
+use crate::handler::ServersHandler;
+use crate::handler::SessionsHandler;
 use crate::handler::StateHandler;
+use crate::handler::TablesHandler;
 use crate::handler::UpdateHandler;
+use crate::handler::UsersHandler;
+use crate::history::HistoryRef;
 use crate::options::Options;
+use crate::postgres::PostgresRef;
 use crate::state::StateRef;
+use crate::tls::TlsServer;
 use iron::Chain;
 use iron::Iron;
-use iron_cors::CorsMiddleware;
 use mount::Mount;
+use router::Router;
 use staticfile::Static;
 
 #[allow(clippy::needless_pass_by_value)]
-pub fn start(options: &Options, config: ConfigRef, state: StateRef) {
+pub fn start(
+    options: &Options,
+    config: SharedConfig,
+    state: StateRef,
+    postgres: PostgresRef,
+    history: Option<HistoryRef>,
+    audit: Option<AuditRef>,
+    tls: Option<TlsServer>,
+) {
     let mut mount = Mount::new();
+    mount.mount("/api/v1/servers", ServersHandler::new(config.clone()));
     mount.mount(
         "/api/v1/state",
         StateHandler::new(config.clone(), state.clone()),
     );
+    mount.mount(
+        "/api/v1/export",
+        ExportHandler::new(config.clone(), state.clone()),
+    );
     mount.mount(
         "/api/v1/update",
-        UpdateHandler::new(config.clone(), state.clone()),
+        UpdateHandler::new(config.clone(), state.clone(), postgres.clone()),
+    );
+    mount.mount(
+        "/api/v1/users",
+        UsersHandler::new(config.clone(), state.clone()),
+    );
+    mount.mount(
+        "/api/v1/dropdb",
+        DropDbHandler::new(
+            config.clone(),
+            state.clone(),
+            postgres.clone(),
+            audit.clone(),
+        ),
+    );
+
+    let mut databases = Router::new();
+    databases.get(
+        "/:name/sessions",
+        SessionsHandler::new(config.clone(), state.clone(), postgres.clone()),
+        "sessions",
+    );
+    databases.get(
+        "/:name/tables",
+        TablesHandler::new(config.clone(), state.clone(), postgres.clone()),
+        "tables",
+    );
+    databases.get(
+        "/:name/bloat",
+        BloatHandler::new(config.clone(), state.clone(), postgres.clone()),
+        "bloat",
+    );
+    mount.mount("/api/v1/databases", databases);
+
+    if let Some(ref audit) = audit {
+        mount.mount(
+            "/api/v1/audit",
+            AuditHandler::new(config.clone(), audit.clone()),
+        );
+    }
+
+    if let Some(history) = history {
+        mount.mount(
+            "/api/v1/history",
+            HistoryHandler::new(config.clone(), history),
+        );
+    }
+
+    mount.mount(
+        "/api/v1/retention/plan",
+        RetentionPlanHandler::new(config.clone(), state.clone()),
+    );
+    mount.mount("/api/v1/admin/reload", ReloadHandler::new(config.clone()));
+
+    mount.mount(
+        "/api/v1/health",
+        HealthHandler::new(config.clone(), state.clone()),
+    );
+    mount.mount(
+        "/metrics",
+        MetricsHandler::new(config.clone(), state.clone()),
     );
-    mount.mount("/api/v1/dropdb", DropDbHandler::new(config.clone()));
     mount.mount("/static", Static::new("public/static"));
     mount.mount("/", Static::new("public"));
 
-    let chain = make_chain(config, mount);
+    let redirect_port = config.get().https().and_then(HttpsConfig::redirect_port);
+    let chain = make_chain(config, audit, mount);
     let address = options.address();
     let port = options.port();
 
-    println!("Listening on {}:{}...", address, port);
+    let tls = match tls {
+        Some(tls) => tls,
+        None => {
+            println!("Listening on {}:{}...", address, port);
 
-    match Iron::new(chain).http((address, port)) {
-        Ok(_) => {}
-        Err(err) => error!("Failed to start HTTP server: {}", err),
-    }
-}
-
-fn make_chain(config: ConfigRef, mount: Mount) -> Chain {
-    let mut chain = Chain::new(mount);
+            if let Err(err) = Iron::new(chain).http((address, port)) {
+                error!("Failed to start HTTP server: {}", err);
+            }
 
-    match config.cors() {
-        Some(Cors::AllowAny) => {
-            chain.link_around(CorsMiddleware::with_allow_any());
+            return;
         }
-        Some(Cors::Whitelist { ref whitelist }) => {
-            chain.link_around(CorsMiddleware::with_whitelist(whitelist.clone()));
+    };
+
+    // Listener stops when it is dropped, so redirect listener must live until HTTPS listener stops.
+    let _redirect = match redirect_port {
+        Some(redirect_port) => {
+            println!("Redirecting {}:{} to HTTPS...", address, redirect_port);
+
+            match Iron::new(HttpsRedirectHandler::new(port)).http((address, redirect_port)) {
+                Ok(listening) => Some(listening),
+                Err(err) => {
+                    error!("Failed to start HTTP redirect server: {}", err);
+
+                    None
+                }
+            }
         }
-        None => {}
+        None => None,
+    };
+
+    println!("Listening on https://{}:{}...", address, port);
+
+    if let Err(err) = Iron::new(chain).https((address, port), tls) {
+        error!("Failed to start HTTPS server: {}", err);
     }
+}
+
+fn make_chain(config: SharedConfig, audit: Option<AuditRef>, mount: Mount) -> Chain {
+    let mut chain = Chain::new(mount);
+    chain.link_before(AuthMiddleware::new(config.clone(), audit));
+    chain.link_around(CorsMiddleware::new(config));
 
     chain
 }
@@ -0,0 +1,110 @@
+use crate::postgres::DatabaseStats;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Activity {
+    stats: Option<DatabaseStats>,
+    last_active: Option<i64>,
+}
+
+impl Activity {
+    /// Returns UNIX time of the last cycle when database counters changed.
+    pub fn last_active(&self) -> Option<i64> {
+        self.last_active
+    }
+
+    /// Compare statistics with the previous sample and update last activity time. If `own_queries` is set, counters
+    /// could be changed by the application, so the sample is only remembered for comparison with the next one.
+    pub(super) fn update(&mut self, stats: DatabaseStats, own_queries: bool, now: i64) {
+        if let Some(previous) = self.stats.as_ref().filter(|_| !own_queries) {
+            if previous.stats_reset() != stats.stats_reset() {
+                // Counters were reset, so any operation was made after reset.
+                if stats.operations() > 0 {
+                    self.last_active = self.last_active.max(stats.stats_reset());
+                }
+            } else if previous.operations() != stats.operations() {
+                self.last_active = Some(now);
+            }
+        }
+
+        self.stats = Some(stats);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(xact_commit: i64, stats_reset: Option<i64>, now: i64) -> Activity {
+        let mut activity = Activity::default();
+        activity.update(DatabaseStats::new(xact_commit, stats_reset), false, now);
+        activity
+    }
+
+    #[test]
+    fn first_sample_is_not_activity() {
+        let activity = activity(10, Some(100), 1000);
+
+        assert_eq!(activity.last_active(), None);
+    }
+
+    #[test]
+    fn unchanged_counters_are_not_activity() {
+        let mut activity = activity(10, Some(100), 1000);
+        activity.update(DatabaseStats::new(10, Some(100)), false, 1060);
+
+        assert_eq!(activity.last_active(), None);
+    }
+
+    #[test]
+    fn increased_counters_are_activity() {
+        let mut activity = activity(10, Some(100), 1000);
+        activity.update(DatabaseStats::new(15, Some(100)), false, 1060);
+        activity.update(DatabaseStats::new(15, Some(100)), false, 1120);
+
+        assert_eq!(activity.last_active(), Some(1060));
+    }
+
+    #[test]
+    fn reset_counters_with_operations_are_activity_after_reset() {
+        let mut activity = activity(10, Some(100), 1000);
+        activity.update(DatabaseStats::new(3, Some(1030)), false, 1060);
+
+        assert_eq!(activity.last_active(), Some(1030));
+    }
+
+    #[test]
+    fn reset_counters_without_operations_are_not_activity() {
+        let mut activity = activity(10, Some(100), 1000);
+        activity.update(DatabaseStats::new(0, Some(1030)), false, 1060);
+        activity.update(DatabaseStats::new(0, Some(1030)), false, 1120);
+
+        assert_eq!(activity.last_active(), None);
+    }
+
+    #[test]
+    fn reset_counters_keep_later_activity() {
+        let mut activity = activity(10, Some(100), 1000);
+        activity.update(DatabaseStats::new(15, Some(100)), false, 1060);
+        activity.update(DatabaseStats::new(3, Some(1030)), false, 1120);
+
+        assert_eq!(activity.last_active(), Some(1060));
+    }
+
+    #[test]
+    fn counters_changed_by_own_queries_are_not_activity() {
+        let mut activity = activity(10, Some(100), 1000);
+        activity.update(DatabaseStats::new(15, Some(100)), true, 1060);
+        activity.update(DatabaseStats::new(15, Some(100)), false, 1120);
+
+        assert_eq!(activity.last_active(), None);
+    }
+
+    #[test]
+    fn counters_changed_after_own_queries_are_activity() {
+        let mut activity = activity(10, Some(100), 1000);
+        activity.update(DatabaseStats::new(15, Some(100)), true, 1060);
+        activity.update(DatabaseStats::new(20, Some(100)), false, 1120);
+
+        assert_eq!(activity.last_active(), Some(1120));
+    }
+}
@@ -0,0 +1,26 @@
+/// Value which is considered valid for limited time.
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    time: i64,
+    value: T,
+}
+
+impl<T> Cached<T> {
+    pub fn new(value: T, time: i64) -> Cached<T> {
+        Cached { time, value }
+    }
+
+    /// Returns value regardless of its age.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns value if it is not older than `max_age` seconds.
+    pub fn fresh(&self, now: i64, max_age: u64) -> Option<&T> {
+        if now - self.time < max_age as i64 {
+            Some(&self.value)
+        } else {
+            None
+        }
+    }
+}
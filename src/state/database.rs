@@ -1,34 +1,84 @@
-#[derive(Debug)]
+use crate::postgres::DatabaseInfo;
+use crate::postgres::SessionStats;
+
+#[derive(Debug, Clone)]
 pub struct Database {
-    name: String,
+    info: DatabaseInfo,
     user: Option<String>,
-    modified: i64,
-    size: u64,
+    attribution: Option<String>,
+    sessions: SessionStats,
+    last_active: Option<i64>,
+    reclaimable: Option<u64>,
 }
 
 impl Database {
-    pub fn new(name: &str, user: Option<&String>, modified: i64, size: u64) -> Database {
+    pub fn new(
+        info: DatabaseInfo,
+        user: Option<&str>,
+        attribution: Option<&str>,
+        sessions: SessionStats,
+    ) -> Database {
         Database {
-            name: name.into(),
-            user: user.cloned(),
-            modified,
-            size,
+            info,
+            user: user.map(String::from),
+            attribution: attribution.map(String::from),
+            sessions,
+            last_active: None,
+            reclaimable: None,
         }
     }
 
     pub fn name(&self) -> &str {
-        &self.name
+        self.info.name()
     }
 
     pub fn user(&self) -> Option<&String> {
         self.user.as_ref()
     }
 
+    /// Returns name of the rule which attributed database to the user.
+    pub fn attribution(&self) -> Option<&String> {
+        self.attribution.as_ref()
+    }
+
     pub fn modified(&self) -> i64 {
-        self.modified
+        self.info.modified()
     }
 
     pub fn size(&self) -> u64 {
-        self.size
+        self.info.size()
+    }
+
+    pub fn sessions(&self) -> &SessionStats {
+        &self.sessions
+    }
+
+    /// Returns UNIX time of the last detected activity in database.
+    pub fn last_active(&self) -> Option<i64> {
+        self.last_active
+    }
+
+    #[cfg(test)]
+    pub fn with_last_active(mut self, last_active: Option<i64>) -> Database {
+        self.last_active = last_active;
+        self
+    }
+
+    pub(super) fn set_last_active(&mut self, last_active: Option<i64>) {
+        self.last_active = last_active;
+    }
+
+    /// Returns estimated number of bytes occupied by bloat of tables and indexes.
+    pub fn reclaimable(&self) -> Option<u64> {
+        self.reclaimable
+    }
+
+    pub(super) fn set_reclaimable(&mut self, reclaimable: Option<u64>) {
+        self.reclaimable = reclaimable;
+    }
+
+    /// Returns database properties queried from the server.
+    pub fn info(&self) -> &DatabaseInfo {
+        &self.info
     }
 }
@@ -0,0 +1,94 @@
+/// Disk state of the server defined in configuration or reported by disk command.
+#[derive(Debug, Default, Clone)]
+pub struct DiskState {
+    offset: u64,
+    capacity: u64,
+    soft_threshold: u64,
+    hard_threshold: u64,
+    used: Option<u64>,
+    free: Option<u64>,
+    mounts: Vec<Mount>,
+}
+
+impl DiskState {
+    pub fn new(offset: u64, capacity: u64, soft_threshold: u64, hard_threshold: u64) -> DiskState {
+        DiskState {
+            offset,
+            capacity,
+            soft_threshold,
+            hard_threshold,
+            used: None,
+            free: None,
+            mounts: Vec::new(),
+        }
+    }
+
+    pub fn with_usage(self, used: Option<u64>, free: Option<u64>) -> DiskState {
+        DiskState { used, free, ..self }
+    }
+
+    pub fn with_mounts(self, mounts: Vec<Mount>) -> DiskState {
+        DiskState { mounts, ..self }
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    pub fn soft_threshold(&self) -> u64 {
+        self.soft_threshold
+    }
+
+    pub fn hard_threshold(&self) -> u64 {
+        self.hard_threshold
+    }
+
+    /// Returns used disk space reported by disk command. If only free space is reported, used space is calculated
+    /// from capacity.
+    pub fn used(&self) -> Option<u64> {
+        self.used
+            .or_else(|| self.free.map(|free| self.capacity.saturating_sub(free)))
+    }
+
+    pub fn mounts(&self) -> &[Mount] {
+        &self.mounts
+    }
+}
+
+/// Usage of single mount point reported by disk command.
+#[derive(Debug, Clone, Serialize)]
+pub struct Mount {
+    path: String,
+    capacity: u64,
+    used: Option<u64>,
+    free: Option<u64>,
+}
+
+impl Mount {
+    pub fn new(path: &str, capacity: u64, used: Option<u64>, free: Option<u64>) -> Mount {
+        Mount {
+            path: path.into(),
+            capacity,
+            used,
+            free,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Returns used space of the mount point, calculated from free space if only it is reported.
+    pub fn used(&self) -> Option<u64> {
+        self.used
+            .or_else(|| self.free.map(|free| self.capacity.saturating_sub(free)))
+    }
+}
@@ -1,11 +1,34 @@
+mod activity;
+mod cache;
 mod database;
+mod disk;
 mod error;
+mod status;
+mod usage;
 
+pub use self::activity::Activity;
+pub use self::cache::Cached;
 pub use self::database::Database;
+pub use self::disk::DiskState;
+pub use self::disk::Mount;
 pub use self::error::StateError;
 pub use self::error::StateResult;
+pub use self::status::Timeout;
+pub use self::status::WorkerStage;
+pub use self::status::WorkerStatus;
+pub use self::usage::UserUsage;
 
+use crate::clock;
+use crate::config::DiskConfig;
+use crate::config::ServerConfig;
+use crate::postgres::Bloat;
+use crate::postgres::DatabaseStats;
+use crate::postgres::TableSize;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::RwLock;
 
@@ -43,125 +66,427 @@ impl StateRef {
         }
     }
 
-    pub fn disk_state(&self) -> StateResult<DiskState> {
-        self.with_read(move |state| Ok(state.disk_state()))
+    pub fn disk_state(&self, server: &str) -> StateResult<DiskState> {
+        self.with_read(move |state| Ok(state.disk_state(server)))
     }
 
-    pub fn set_disk_state(
+    pub fn set_disk_state(&self, server: &str, disk_state: DiskState) -> StateResult<()> {
+        self.with_write(move |state| {
+            state.server_mut(server).disk_state = disk_state;
+
+            Ok(())
+        })
+    }
+
+    /// Returns used disk space of the server. For fixed disk configuration sizes of all databases are added to disk
+    /// offset. For disk command used space reported by command is returned, if command reports neither used nor free
+    /// space, offset is returned.
+    pub fn disk_used(&self, server_config: &ServerConfig) -> StateResult<u64> {
+        self.with_read(move |state| Ok(state.disk_used(server_config)))
+    }
+
+    pub fn worker_status(&self, server: &str) -> StateResult<WorkerStatus> {
+        self.with_read(move |state| Ok(state.worker_status(server)))
+    }
+
+    pub fn complete_cycle(
         &self,
-        offset: u64,
-        capacity: u64,
-        soft_threshold: u64,
-        hard_threshold: u64,
+        server: &str,
+        time: i64,
+        duration: f64,
+        success: bool,
     ) -> StateResult<()> {
         self.with_write(move |state| {
-            state.set_disk_state(offset, capacity, soft_threshold, hard_threshold);
+            state
+                .server_mut(server)
+                .worker_status
+                .complete_cycle(time, duration, success);
+
+            Ok(())
+        })
+    }
+
+    pub fn add_success(&self, server: &str, stage: WorkerStage, time: i64) -> StateResult<()> {
+        self.with_write(move |state| {
+            state
+                .server_mut(server)
+                .worker_status
+                .add_success(stage, time);
 
             Ok(())
         })
     }
 
-    pub fn for_each<F>(&self, callback: F) -> StateResult<()>
+    pub fn add_error(
+        &self,
+        server: &str,
+        stage: WorkerStage,
+        time: i64,
+        message: &str,
+        timeout: Option<Timeout>,
+    ) -> StateResult<()> {
+        self.with_write(move |state| {
+            state
+                .server_mut(server)
+                .worker_status
+                .add_error(stage, time, message, timeout);
+
+            Ok(())
+        })
+    }
+
+    /// Returns `true` if server data was not updated for more than `stale_after` seconds. Servers which were never
+    /// updated are stale when `stale_after` seconds passed since start.
+    pub fn is_stale(&self, server: &str, now: i64, stale_after: u64) -> StateResult<bool> {
+        self.with_read(move |state| {
+            let updated = state
+                .worker_status(server)
+                .data_updated()
+                .unwrap_or(state.started);
+
+            Ok(now - updated > stale_after as i64)
+        })
+    }
+
+    pub fn for_each<F>(&self, server: &str, callback: F) -> StateResult<()>
     where
         F: FnMut(&Database),
     {
         self.with_read(move |state| {
-            state.for_each(callback);
+            state.for_each(server, callback);
+
+            Ok(())
+        })
+    }
+
+    /// Returns total size and number of databases of every user over all servers.
+    pub fn user_usage(&self) -> StateResult<HashMap<String, UserUsage>> {
+        self.with_read(move |state| Ok(state.user_usage()))
+    }
+
+    pub fn database(&self, server: &str, name: &str) -> StateResult<Option<Database>> {
+        self.with_read(move |state| Ok(state.database(server, name)))
+    }
+
+    pub fn put(&self, server: &str, database: Database) -> StateResult<()> {
+        self.with_write(move |state| {
+            state.put(server, database);
 
             Ok(())
         })
     }
 
-    pub fn put(
+    /// Update activity of the server databases using new statistics. Activity of databases which are not in
+    /// statistics will be removed. Changes of counters of `queried` databases are caused by the application itself, so
+    /// they are not considered as activity.
+    pub fn update_activity(
         &self,
-        name: &str,
-        user: Option<&String>,
-        modified: i64,
-        size: u64,
+        server: &str,
+        stats: HashMap<String, DatabaseStats>,
+        queried: &HashSet<String>,
+        now: i64,
     ) -> StateResult<()> {
-        self.with_write(move |state| Ok(state.put(name, user, modified, size)))
+        self.with_write(move |state| {
+            state.update_activity(server, stats, queried, now);
+
+            Ok(())
+        })
     }
 
-    pub fn clear(&self) -> StateResult<()> {
-        self.with_write(move |state| Ok(state.clear()))
+    /// Load activity of all servers from file. Missing file is not an error.
+    pub fn load_activity(&self, path: &Path) -> StateResult<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let reader = File::open(path).map_err(|err| {
+            StateError::new(&format!("Failed to open {} - {}", path.display(), err))
+        })?;
+        let activity: HashMap<String, HashMap<String, Activity>> = serde_json::from_reader(reader)
+            .map_err(|err| {
+                StateError::new(&format!("Failed to read {} - {}", path.display(), err))
+            })?;
+
+        self.with_write(move |state| {
+            for (server, databases) in activity {
+                state.server_mut(&server).activity = databases;
+            }
+
+            Ok(())
+        })
     }
-}
 
-#[derive(Debug, Default, Clone)]
-pub struct DiskState {
-    offset: u64,
-    capacity: u64,
-    soft_threshold: u64,
-    hard_threshold: u64,
-}
+    /// Save activity of all servers to file.
+    pub fn save_activity(&self, path: &Path) -> StateResult<()> {
+        let activity = self.with_read(move |state| Ok(state.activity()))?;
+        let temp_path = path.with_extension("tmp");
+        let writer = File::create(&temp_path).map_err(|err| {
+            StateError::new(&format!(
+                "Failed to create {} - {}",
+                temp_path.display(),
+                err
+            ))
+        })?;
 
-impl DiskState {
-    pub fn offset(&self) -> u64 {
-        self.offset
+        serde_json::to_writer(writer, &activity).map_err(|err| {
+            StateError::new(&format!(
+                "Failed to write {} - {}",
+                temp_path.display(),
+                err
+            ))
+        })?;
+        fs::rename(&temp_path, path).map_err(|err| {
+            StateError::new(&format!("Failed to replace {} - {}", path.display(), err))
+        })
     }
 
-    pub fn capacity(&self) -> u64 {
-        self.capacity
+    /// Returns table sizes of the database if they were stored less than `max_age` seconds ago.
+    pub fn tables(
+        &self,
+        server: &str,
+        name: &str,
+        now: i64,
+        max_age: u64,
+    ) -> StateResult<Option<Vec<TableSize>>> {
+        self.with_read(move |state| Ok(state.tables(server, name, now, max_age)))
     }
 
-    pub fn soft_threshold(&self) -> u64 {
-        self.soft_threshold
+    pub fn set_tables(
+        &self,
+        server: &str,
+        name: &str,
+        tables: Vec<TableSize>,
+        now: i64,
+    ) -> StateResult<()> {
+        self.with_write(move |state| {
+            state
+                .server_mut(server)
+                .tables
+                .insert(name.into(), Cached::new(tables, now));
+
+            Ok(())
+        })
     }
 
-    pub fn hard_threshold(&self) -> u64 {
-        self.hard_threshold
+    /// Returns bloat of the database if it was stored less than `max_age` seconds ago.
+    pub fn bloat(
+        &self,
+        server: &str,
+        name: &str,
+        now: i64,
+        max_age: u64,
+    ) -> StateResult<Option<Vec<Bloat>>> {
+        self.with_read(move |state| Ok(state.bloat(server, name, now, max_age)))
+    }
+
+    /// Store bloat of the database. Reclaimable size of the database will be updated immediately.
+    pub fn set_bloat(
+        &self,
+        server: &str,
+        name: &str,
+        bloat: Vec<Bloat>,
+        now: i64,
+    ) -> StateResult<()> {
+        self.with_write(move |state| {
+            let server_state = state.server_mut(server);
+            let reclaimable = bloat.iter().map(Bloat::wasted).sum();
+
+            if let Some(database) = server_state.databases.get_mut(name) {
+                database.set_reclaimable(Some(reclaimable));
+            }
+
+            server_state
+                .bloat
+                .insert(name.into(), Cached::new(bloat, now));
+
+            Ok(())
+        })
+    }
+
+    pub fn remove(&self, server: &str, name: &str) -> StateResult<()> {
+        self.with_write(move |state| {
+            state.remove(server, name);
+
+            Ok(())
+        })
+    }
+
+    pub fn clear(&self, server: &str) -> StateResult<()> {
+        self.with_write(move |state| {
+            state.clear(server);
+
+            Ok(())
+        })
     }
 }
 
-#[derive(Debug)]
-struct State {
+#[derive(Debug, Default)]
+struct ServerState {
     databases: HashMap<String, Database>,
     disk_state: DiskState,
+    worker_status: WorkerStatus,
+    activity: HashMap<String, Activity>,
+    tables: HashMap<String, Cached<Vec<TableSize>>>,
+    bloat: HashMap<String, Cached<Vec<Bloat>>>,
+}
+
+#[derive(Debug)]
+struct State {
+    servers: HashMap<String, ServerState>,
+    started: i64,
 }
 
 impl State {
     #[allow(clippy::needless_pass_by_value)]
     fn new() -> State {
         State {
-            databases: HashMap::new(),
-            disk_state: DiskState::default(),
+            servers: HashMap::new(),
+            started: clock::unix_timestamp(),
         }
     }
 
-    pub fn disk_state(&self) -> DiskState {
-        self.disk_state.clone()
+    fn server_mut(&mut self, server: &str) -> &mut ServerState {
+        self.servers.entry(server.into()).or_default()
     }
 
-    pub fn set_disk_state(
-        &mut self,
-        offset: u64,
-        capacity: u64,
-        soft_threshold: u64,
-        hard_threshold: u64,
-    ) {
-        self.disk_state.offset = offset;
-        self.disk_state.capacity = capacity;
-        self.disk_state.soft_threshold = soft_threshold;
-        self.disk_state.hard_threshold = hard_threshold;
+    pub fn disk_state(&self, server: &str) -> DiskState {
+        self.servers
+            .get(server)
+            .map(|server_state| server_state.disk_state.clone())
+            .unwrap_or_default()
     }
 
-    pub fn for_each<F>(&self, mut callback: F)
+    pub fn disk_used(&self, server_config: &ServerConfig) -> u64 {
+        let server_state = match self.servers.get(server_config.name()) {
+            Some(server_state) => server_state,
+            None => return 0,
+        };
+        let disk_state = &server_state.disk_state;
+
+        match server_config.disk() {
+            DiskConfig::Fixed { .. } => {
+                disk_state.offset()
+                    + server_state
+                        .databases
+                        .values()
+                        .map(|d| d.size())
+                        .sum::<u64>()
+            }
+            DiskConfig::Command { .. } => disk_state.used().unwrap_or_else(|| disk_state.offset()),
+        }
+    }
+
+    pub fn worker_status(&self, server: &str) -> WorkerStatus {
+        self.servers
+            .get(server)
+            .map(|server_state| server_state.worker_status.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn for_each<F>(&self, server: &str, mut callback: F)
     where
         F: FnMut(&Database),
     {
-        for database in self.databases.values() {
-            callback(database);
+        if let Some(server_state) = self.servers.get(server) {
+            for database in server_state.databases.values() {
+                callback(database);
+            }
+        }
+    }
+
+    pub fn user_usage(&self) -> HashMap<String, UserUsage> {
+        let mut result: HashMap<String, UserUsage> = HashMap::new();
+
+        for server_state in self.servers.values() {
+            for database in server_state.databases.values() {
+                if let Some(user) = database.user() {
+                    result.entry(user.clone()).or_default().add(database.size());
+                }
+            }
+        }
+
+        result
+    }
+
+    pub fn database(&self, server: &str, name: &str) -> Option<Database> {
+        self.servers
+            .get(server)
+            .and_then(|server_state| server_state.databases.get(name))
+            .cloned()
+    }
+
+    fn put(&mut self, server: &str, mut database: Database) {
+        let server_state = self.server_mut(server);
+        let last_active = server_state
+            .activity
+            .get(database.name())
+            .and_then(Activity::last_active);
+
+        let reclaimable = server_state
+            .bloat
+            .get(database.name())
+            .map(|bloat| bloat.value().iter().map(Bloat::wasted).sum());
+
+        database.set_last_active(last_active);
+        database.set_reclaimable(reclaimable);
+        server_state
+            .databases
+            .insert(database.name().into(), database);
+    }
+
+    fn update_activity(
+        &mut self,
+        server: &str,
+        stats: HashMap<String, DatabaseStats>,
+        queried: &HashSet<String>,
+        now: i64,
+    ) {
+        let activity = &mut self.server_mut(server).activity;
+        activity.retain(|name, _| stats.contains_key(name));
+
+        for (name, database_stats) in stats {
+            let own_queries = queried.contains(&name);
+
+            activity
+                .entry(name)
+                .or_default()
+                .update(database_stats, own_queries, now);
         }
     }
 
-    fn put(&mut self, name: &str, user: Option<&String>, modified: i64, size: u64) {
-        let database = Database::new(name, user, modified, size);
+    fn activity(&self) -> HashMap<String, HashMap<String, Activity>> {
+        self.servers
+            .iter()
+            .map(|(server, server_state)| (server.clone(), server_state.activity.clone()))
+            .collect()
+    }
+
+    fn tables(&self, server: &str, name: &str, now: i64, max_age: u64) -> Option<Vec<TableSize>> {
+        self.servers
+            .get(server)
+            .and_then(|server_state| server_state.tables.get(name))
+            .and_then(|tables| tables.fresh(now, max_age))
+            .cloned()
+    }
+
+    fn bloat(&self, server: &str, name: &str, now: i64, max_age: u64) -> Option<Vec<Bloat>> {
+        self.servers
+            .get(server)
+            .and_then(|server_state| server_state.bloat.get(name))
+            .and_then(|bloat| bloat.fresh(now, max_age))
+            .cloned()
+    }
+
+    fn remove(&mut self, server: &str, name: &str) {
+        let server_state = self.server_mut(server);
 
-        self.databases.insert(name.into(), database);
+        server_state.databases.remove(name);
+        server_state.tables.remove(name);
+        server_state.bloat.remove(name);
     }
 
-    fn clear(&mut self) {
-        self.databases.clear();
+    fn clear(&mut self, server: &str) {
+        self.server_mut(server).databases.clear();
     }
 }
 
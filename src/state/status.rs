@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct WorkerStatus {
+    last_attempt: Option<i64>,
+    last_success: Option<i64>,
+    cycle_duration: Option<f64>,
+    stages: BTreeMap<WorkerStage, StageStatus>,
+    connect_timeouts: u64,
+    statement_timeouts: u64,
+    disk_command_timeouts: u64,
+}
+
+impl WorkerStatus {
+    /// Returns UNIX time of the last update cycle completed without errors.
+    pub fn last_success(&self) -> Option<i64> {
+        self.last_success
+    }
+
+    /// Returns duration of the last update cycle in seconds.
+    pub fn cycle_duration(&self) -> Option<f64> {
+        self.cycle_duration
+    }
+
+    /// Returns status of the update stage. Stages which were never run have default status.
+    pub fn stage(&self, stage: WorkerStage) -> StageStatus {
+        self.stages.get(&stage).cloned().unwrap_or_default()
+    }
+
+    /// Returns UNIX time when both disk usage and databases were updated successfully.
+    pub fn data_updated(&self) -> Option<i64> {
+        let disk = self.stage(WorkerStage::Disk).last_success?;
+        let databases = self.stage(WorkerStage::Databases).last_success?;
+
+        Some(disk.min(databases))
+    }
+
+    pub fn connect_timeouts(&self) -> u64 {
+        self.connect_timeouts
+    }
+
+    pub fn statement_timeouts(&self) -> u64 {
+        self.statement_timeouts
+    }
+
+    pub fn disk_command_timeouts(&self) -> u64 {
+        self.disk_command_timeouts
+    }
+
+    pub(super) fn complete_cycle(&mut self, time: i64, duration: f64, success: bool) {
+        self.last_attempt = Some(time);
+        self.cycle_duration = Some(duration);
+
+        if success {
+            self.last_success = Some(time);
+        }
+    }
+
+    pub(super) fn add_success(&mut self, stage: WorkerStage, time: i64) {
+        let status = self.stages.entry(stage).or_default();
+
+        status.last_attempt = Some(time);
+        status.last_success = Some(time);
+        status.consecutive_failures = 0;
+    }
+
+    pub(super) fn add_error(
+        &mut self,
+        stage: WorkerStage,
+        time: i64,
+        message: &str,
+        timeout: Option<Timeout>,
+    ) {
+        match timeout {
+            Some(Timeout::Connect) => self.connect_timeouts += 1,
+            Some(Timeout::Statement) => self.statement_timeouts += 1,
+            Some(Timeout::DiskCommand) => self.disk_command_timeouts += 1,
+            None => {}
+        }
+
+        let status = self.stages.entry(stage).or_default();
+
+        status.last_attempt = Some(time);
+        status.last_error = Some(message.into());
+        status.last_error_time = Some(time);
+        status.consecutive_failures += 1;
+        status.errors += 1;
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct StageStatus {
+    last_attempt: Option<i64>,
+    last_success: Option<i64>,
+    last_error: Option<String>,
+    last_error_time: Option<i64>,
+    consecutive_failures: u64,
+    errors: u64,
+}
+
+impl StageStatus {
+    pub fn last_success(&self) -> Option<i64> {
+        self.last_success
+    }
+
+    pub fn consecutive_failures(&self) -> u64 {
+        self.consecutive_failures
+    }
+
+    /// Returns total number of failures of the stage.
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkerStage {
+    Disk,
+    Databases,
+    History,
+    Retention,
+    Bloat,
+}
+
+impl WorkerStage {
+    pub const ALL: [WorkerStage; 5] = [
+        WorkerStage::Disk,
+        WorkerStage::Databases,
+        WorkerStage::History,
+        WorkerStage::Retention,
+        WorkerStage::Bloat,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WorkerStage::Disk => "disk",
+            WorkerStage::Databases => "databases",
+            WorkerStage::History => "history",
+            WorkerStage::Retention => "retention",
+            WorkerStage::Bloat => "bloat",
+        }
+    }
+}
+
+/// Kind of timeout which caused failure of update stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timeout {
+    Connect,
+    Statement,
+    DiskCommand,
+}
@@ -0,0 +1,25 @@
+#[derive(Debug, Default, Clone)]
+pub struct UserUsage {
+    size: u64,
+    databases: u64,
+}
+
+impl UserUsage {
+    #[cfg(test)]
+    pub fn new(size: u64, databases: u64) -> UserUsage {
+        UserUsage { size, databases }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn databases(&self) -> u64 {
+        self.databases
+    }
+
+    pub(super) fn add(&mut self, size: u64) {
+        self.size += size;
+        self.databases += 1;
+    }
+}
@@ -0,0 +1,177 @@
+use std::borrow::Cow;
+use std::io::Result as IoResult;
+use std::io::Write;
+
+const SIZE_UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// Rows of text cells which are written as aligned table, CSV or TSV.
+#[derive(Debug)]
+pub struct Table {
+    header: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(header: Vec<&'static str>) -> Table {
+        Table {
+            header,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn add_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// Write columns aligned by spaces, numbers should be already formatted for humans.
+    pub fn write_text<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        let mut widths: Vec<usize> = self.header.iter().map(|cell| cell.len()).collect();
+
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let header: Vec<String> = self.header.iter().map(|cell| cell.to_string()).collect();
+
+        for row in Some(&header).into_iter().chain(&self.rows) {
+            let line: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect();
+
+            writeln!(writer, "{}", line.join("  ").trim_end())?;
+        }
+
+        Ok(())
+    }
+
+    /// Write rows as CSV according to RFC 4180. Fields which look like formulas are escaped.
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        let header: Vec<String> = self.header.iter().map(|cell| cell.to_string()).collect();
+
+        for row in Some(&header).into_iter().chain(&self.rows) {
+            let line: Vec<String> = row.iter().map(|cell| csv_field(cell)).collect();
+
+            write!(writer, "{}\r\n", line.join(","))?;
+        }
+
+        Ok(())
+    }
+
+    /// Write rows as tab separated values. Format has no quoting, so tabs and line breaks inside fields are replaced
+    /// with spaces. Fields which look like formulas are escaped.
+    pub fn write_tsv<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        let header: Vec<String> = self.header.iter().map(|cell| cell.to_string()).collect();
+
+        for row in Some(&header).into_iter().chain(&self.rows) {
+            let line: Vec<String> = row
+                .iter()
+                .map(|cell| escape_formula(cell).replace(['\t', '\r', '\n'], " "))
+                .collect();
+
+            writeln!(writer, "{}", line.join("\t"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Quote field if it contains separator, quote or line break. Quotes inside field are doubled.
+fn csv_field(value: &str) -> String {
+    let value = escape_formula(value);
+
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.into()
+    }
+}
+
+/// Prefix field with `'` if spreadsheet would evaluate it as formula.
+fn escape_formula(value: &str) -> Cow<'_, str> {
+    if value.starts_with(FORMULA_PREFIXES) {
+        Cow::Owned(format!("'{}", value))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Format size in bytes using binary units, e.g. `1.5 GiB`. Format is the same as in web interface.
+pub fn human_size(value: u64) -> String {
+    let mut size = value as f64;
+    let mut index = 0;
+
+    while size > 1024.0 && index + 1 < SIZE_UNITS.len() {
+        size /= 1024.0;
+        index += 1;
+    }
+
+    format!("{:.1} {}", size, SIZE_UNITS[index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write<F>(write: F) -> String
+    where
+        F: FnOnce(&mut Vec<u8>) -> IoResult<()>,
+    {
+        let mut output = Vec::new();
+
+        write(&mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn csv_field_quotes_special_characters() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn csv_field_escapes_formulas() {
+        assert_eq!(csv_field("=1+2"), "'=1+2");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-1"), "'-1");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(
+            csv_field("=HYPERLINK(\"x\",1)"),
+            "\"'=HYPERLINK(\"\"x\"\",1)\""
+        );
+        assert_eq!(csv_field("a=b"), "a=b");
+    }
+
+    #[test]
+    fn write_csv_uses_crlf() {
+        let mut table = Table::new(vec!["name", "size"]);
+
+        table.add_row(vec!["db, one".into(), "10".into()]);
+        table.add_row(vec!["=cmd".into(), "20".into()]);
+
+        assert_eq!(
+            write(|output| table.write_csv(output)),
+            "name,size\r\n\"db, one\",10\r\n'=cmd,20\r\n"
+        );
+    }
+
+    #[test]
+    fn write_tsv_replaces_separators() {
+        let mut table = Table::new(vec!["name", "size"]);
+
+        table.add_row(vec!["db\tone\n".into(), "-5".into()]);
+
+        assert_eq!(
+            write(|output| table.write_tsv(output)),
+            "name\tsize\ndb one \t'-5\n"
+        );
+    }
+}
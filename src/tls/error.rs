@@ -0,0 +1,43 @@
+use native_tls::Error as NativeTlsError;
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::io::Error as IoError;
+use std::path::Path;
+
+pub type TlsResult<T> = Result<T, TlsError>;
+
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum TlsError {
+    IoError { message: String },
+    CertificateError { message: String },
+}
+
+impl TlsError {
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn io_error(path: &Path, error: IoError) -> TlsError {
+        TlsError::IoError {
+            message: format!("{}: {}", path.display(), error),
+        }
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn certificate_error(error: NativeTlsError) -> TlsError {
+        TlsError::CertificateError {
+            message: format!("{}", error),
+        }
+    }
+}
+
+impl Error for TlsError {}
+
+impl Display for TlsError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            TlsError::IoError { message } => write!(f, "{}", message),
+            TlsError::CertificateError { message } => write!(f, "{}", message),
+        }
+    }
+}
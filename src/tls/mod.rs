@@ -0,0 +1,113 @@
+mod error;
+
+pub use self::error::TlsError;
+pub use self::error::TlsResult;
+
+use crate::config::HttpsConfig;
+use hyper::net::HttpStream;
+use hyper::net::NetworkStream;
+use hyper::net::SslServer;
+use hyper::Error as HyperError;
+use native_tls::HandshakeError;
+use native_tls::Identity;
+use native_tls::TlsAcceptor;
+use native_tls::TlsStream as NativeTlsStream;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::net::Shutdown;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Duration;
+
+/// TLS wrapper of accepted HTTP connections.
+#[derive(Clone)]
+pub struct TlsServer {
+    acceptor: TlsAcceptor,
+}
+
+impl Debug for TlsServer {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("TlsServer").finish()
+    }
+}
+
+impl SslServer for TlsServer {
+    type Stream = TlsStream;
+
+    fn wrap_server(&self, stream: HttpStream) -> Result<TlsStream, HyperError> {
+        match self.acceptor.accept(stream) {
+            Ok(stream) => Ok(TlsStream {
+                inner: Arc::new(Mutex::new(stream)),
+            }),
+            Err(HandshakeError::Failure(err)) => Err(HyperError::Ssl(Box::new(err))),
+            Err(HandshakeError::WouldBlock(_)) => Err(HyperError::Ssl(
+                "TLS handshake was interrupted on blocking socket".into(),
+            )),
+        }
+    }
+}
+
+/// TLS stream shared between request reader and response writer.
+#[derive(Clone)]
+pub struct TlsStream {
+    inner: Arc<Mutex<NativeTlsStream<HttpStream>>>,
+}
+
+impl TlsStream {
+    fn lock(&self) -> io::Result<MutexGuard<'_, NativeTlsStream<HttpStream>>> {
+        self.inner
+            .lock()
+            .map_err(|_| io::Error::other("Failed to acquire TLS stream lock"))
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.lock()?.read(buf)
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock()?.flush()
+    }
+}
+
+impl NetworkStream for TlsStream {
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        self.lock()?.get_mut().peer_addr()
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.lock()?.get_ref().set_read_timeout(dur)
+    }
+
+    fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.lock()?.get_ref().set_write_timeout(dur)
+    }
+
+    fn close(&mut self, how: Shutdown) -> io::Result<()> {
+        self.lock()?.get_mut().close(how)
+    }
+}
+
+/// Create TLS server using PEM certificate chain and PEM (PKCS #8) private key from configuration.
+pub fn create(config: &HttpsConfig) -> TlsResult<TlsServer> {
+    let cert = fs::read(config.cert()).map_err(|err| TlsError::io_error(config.cert(), err))?;
+    let key = fs::read(config.key()).map_err(|err| TlsError::io_error(config.key(), err))?;
+    let identity = Identity::from_pkcs8(&cert, &key).map_err(TlsError::certificate_error)?;
+    let acceptor = TlsAcceptor::new(identity).map_err(TlsError::certificate_error)?;
+
+    Ok(TlsServer { acceptor })
+}
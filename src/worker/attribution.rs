@@ -0,0 +1,147 @@
+use crate::config::AttributionRule;
+use crate::config::Config;
+use crate::config::UserConfig;
+use crate::postgres::DatabaseInfo;
+
+#[derive(Debug, Clone)]
+pub struct Attribution {
+    user: String,
+    rule: &'static str,
+}
+
+impl Attribution {
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    /// Returns name of the rule which attributed database to the user.
+    pub fn rule(&self) -> &'static str {
+        self.rule
+    }
+}
+
+/// Find user of the database. Rules are applied in configured order, the first rule which matches a known user wins.
+pub fn attribute(config: &Config, info: &DatabaseInfo) -> Option<Attribution> {
+    let users = config.users();
+
+    config.attribution_rules().iter().find_map(|rule| {
+        apply_rule(users, rule, info).map(|user| Attribution {
+            user,
+            rule: rule.name(),
+        })
+    })
+}
+
+fn apply_rule(users: &[UserConfig], rule: &AttributionRule, info: &DatabaseInfo) -> Option<String> {
+    let database_name = info.name().to_lowercase();
+
+    match rule {
+        AttributionRule::Override { databases } => databases
+            .get(info.name())
+            .and_then(|login| find_user(users, login)),
+        AttributionRule::Owner => find_user(users, info.owner()),
+        AttributionRule::Comment { pattern } => info
+            .comment()
+            .and_then(|comment| pattern.capture_user(comment))
+            .and_then(|login| find_user(users, login)),
+        AttributionRule::Pattern { pattern } => pattern
+            .capture_user(info.name())
+            .and_then(|login| find_user(users, login)),
+        AttributionRule::UserPattern => users
+            .iter()
+            .find(|user| {
+                user.patterns()
+                    .iter()
+                    .any(|pattern| pattern.is_match(info.name()))
+            })
+            .map(|user| user.login().to_lowercase()),
+        AttributionRule::Prefix => longest_match(users, |name| database_name.starts_with(name)),
+        AttributionRule::Suffix => longest_match(users, |name| database_name.ends_with(name)),
+        AttributionRule::Contains => longest_match(users, |name| database_name.contains(name)),
+    }
+}
+
+fn find_user(users: &[UserConfig], login: &str) -> Option<String> {
+    users
+        .iter()
+        .find(|user| user.login().eq_ignore_ascii_case(login))
+        .map(|user| user.login().to_lowercase())
+}
+
+/// Returns login of the user with the longest matched login or alias. If several users match names of the same
+/// length, the first user in configuration wins.
+fn longest_match<F>(users: &[UserConfig], matches: F) -> Option<String>
+where
+    F: Fn(&str) -> bool,
+{
+    let mut result: Option<(&UserConfig, usize)> = None;
+
+    for user in users {
+        let names = Some(user.login())
+            .into_iter()
+            .chain(user.aliases().iter().map(String::as_str));
+
+        for name in names {
+            let name = name.to_lowercase();
+
+            if !name.is_empty()
+                && matches(&name)
+                && result.is_none_or(|(_, length)| name.len() > length)
+            {
+                result = Some((user, name.len()));
+            }
+        }
+    }
+
+    result.map(|(user, _)| user.login().to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users(yaml: &str) -> Vec<UserConfig> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn longest_match_prefers_longest_name() {
+        let users = users("- login: ann\n- login: Anna\n- login: bob");
+
+        assert_eq!(
+            longest_match(&users, |name| "anna_test".contains(name)),
+            Some("anna".into())
+        );
+        assert_eq!(
+            longest_match(&users, |name| "ann_test".contains(name)),
+            Some("ann".into())
+        );
+    }
+
+    #[test]
+    fn longest_match_uses_aliases() {
+        let users = users("- login: ann\n- login: robert\n  aliases: [annabel]");
+
+        assert_eq!(
+            longest_match(&users, |name| "annabel_db".starts_with(name)),
+            Some("robert".into())
+        );
+    }
+
+    #[test]
+    fn longest_match_prefers_first_user_on_equal_length() {
+        let users = users("- login: bob\n- login: ann\n  aliases: [bob]");
+
+        assert_eq!(
+            longest_match(&users, |name| "bob_db".contains(name)),
+            Some("bob".into())
+        );
+    }
+
+    #[test]
+    fn longest_match_ignores_empty_names() {
+        let users = users("- login: ann\n  aliases: [\"\"]");
+
+        assert_eq!(longest_match(&users, |name| "db".contains(name)), None);
+    }
+}
@@ -0,0 +1,329 @@
+use super::WorkerError;
+use super::WorkerResult;
+use crate::state::DiskState;
+use crate::state::Mount;
+use std::collections::HashSet;
+
+/// Parse output of disk command. Output format is detected automatically:
+///
+/// * JSON object with `offset`, `capacity`, `soft_threshold`, `hard_threshold`, optional `used`, `free` and list of
+///   `mounts` with `path`, `capacity` and optional `used` and `free`;
+/// * `key=value` lines with the same keys, mount points are described by `mount.PATH.capacity`, `mount.PATH.used` and
+///   `mount.PATH.free` keys;
+/// * four lines with offset, capacity, soft and hard thresholds.
+///
+/// If capacity is not set, it is calculated as sum of mount point capacities.
+pub fn parse(output: &str) -> WorkerResult<DiskState> {
+    let output = output.trim();
+    let parsed = if output.starts_with('{') {
+        serde_json::from_str(output)
+            .map_err(|err| WorkerError::disk_output_error(&format!("{}", err)))?
+    } else if output.lines().any(|line| line.contains('=')) {
+        parse_key_value(output)?
+    } else {
+        parse_lines(output)?
+    };
+
+    parsed.into_disk_state()
+}
+
+fn parse_key_value(output: &str) -> WorkerResult<Output> {
+    let mut result = Output::default();
+    let mut keys = HashSet::new();
+
+    for (index, line) in output.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(|| {
+            WorkerError::disk_output_error(&format!("line {} is not `key=value`", index + 1))
+        })?;
+        let key = key.trim();
+        let value = parse_number(key, value)?;
+
+        if !keys.insert(key) {
+            return Err(WorkerError::disk_output_error(&format!(
+                "duplicate key `{}`",
+                key
+            )));
+        }
+
+        match key {
+            "offset" => result.offset = Some(value),
+            "capacity" => result.capacity = Some(value),
+            "soft_threshold" => result.soft_threshold = Some(value),
+            "hard_threshold" => result.hard_threshold = Some(value),
+            "used" => result.used = Some(value),
+            "free" => result.free = Some(value),
+            _ => {
+                // Path may contain dots, so field name is taken after the last dot.
+                let (path, field) = key
+                    .strip_prefix("mount.")
+                    .and_then(|mount| mount.rsplit_once('.'))
+                    .ok_or_else(|| {
+                        WorkerError::disk_output_error(&format!("unknown key `{}`", key))
+                    })?;
+                let mount = match result.mounts.iter().position(|mount| mount.path == path) {
+                    Some(position) => &mut result.mounts[position],
+                    None => {
+                        result.mounts.push(MountOutput::new(path));
+                        result.mounts.last_mut().unwrap()
+                    }
+                };
+
+                match field {
+                    "capacity" => mount.capacity = Some(value),
+                    "used" => mount.used = Some(value),
+                    "free" => mount.free = Some(value),
+                    _ => {
+                        return Err(WorkerError::disk_output_error(&format!(
+                            "unknown key `{}`",
+                            key
+                        )))
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn parse_lines(output: &str) -> WorkerResult<Output> {
+    let lines: Vec<&str> = output
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    if lines.len() != 4 {
+        return Err(WorkerError::disk_output_error(
+            "expected four lines with offset, capacity, soft and hard thresholds",
+        ));
+    }
+
+    let values = lines
+        .iter()
+        .zip(&["offset", "capacity", "soft_threshold", "hard_threshold"])
+        .map(|(line, key)| parse_number(key, line))
+        .collect::<WorkerResult<Vec<u64>>>()?;
+
+    Ok(Output {
+        offset: Some(values[0]),
+        capacity: Some(values[1]),
+        soft_threshold: Some(values[2]),
+        hard_threshold: Some(values[3]),
+        ..Output::default()
+    })
+}
+
+fn parse_number(key: &str, value: &str) -> WorkerResult<u64> {
+    value.trim().parse().map_err(|_| {
+        WorkerError::disk_output_error(&format!(
+            "`{}` is not a valid value of `{}`",
+            value.trim(),
+            key
+        ))
+    })
+}
+
+fn required(value: Option<u64>, key: &str) -> WorkerResult<u64> {
+    value.ok_or_else(|| WorkerError::disk_output_error(&format!("`{}` is missing", key)))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Output {
+    offset: Option<u64>,
+    capacity: Option<u64>,
+    soft_threshold: Option<u64>,
+    hard_threshold: Option<u64>,
+    used: Option<u64>,
+    free: Option<u64>,
+    #[serde(default)]
+    mounts: Vec<MountOutput>,
+}
+
+impl Output {
+    fn into_disk_state(self) -> WorkerResult<DiskState> {
+        let mounts = self
+            .mounts
+            .into_iter()
+            .map(MountOutput::into_mount)
+            .collect::<WorkerResult<Vec<Mount>>>()?;
+        let capacity = match self.capacity {
+            Some(capacity) => capacity,
+            None if !mounts.is_empty() => mounts.iter().map(Mount::capacity).sum(),
+            None => return Err(WorkerError::disk_output_error("`capacity` is missing")),
+        };
+        let free = self.free;
+
+        validate_usage("disk", capacity, self.used, free)?;
+
+        // Total usage is known from mount points only when every mount point reports it.
+        let used = self.used.or_else(|| {
+            if free.is_none() && !mounts.is_empty() {
+                mounts.iter().map(Mount::used).sum()
+            } else {
+                None
+            }
+        });
+
+        Ok(DiskState::new(
+            required(self.offset, "offset")?,
+            capacity,
+            required(self.soft_threshold, "soft_threshold")?,
+            required(self.hard_threshold, "hard_threshold")?,
+        )
+        .with_usage(used, free)
+        .with_mounts(mounts))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MountOutput {
+    path: String,
+    capacity: Option<u64>,
+    used: Option<u64>,
+    free: Option<u64>,
+}
+
+impl MountOutput {
+    fn new(path: &str) -> MountOutput {
+        MountOutput {
+            path: path.into(),
+            capacity: None,
+            used: None,
+            free: None,
+        }
+    }
+
+    fn into_mount(self) -> WorkerResult<Mount> {
+        let capacity = required(self.capacity, &format!("mount.{}.capacity", self.path))?;
+
+        validate_usage(&self.path, capacity, self.used, self.free)?;
+
+        Ok(Mount::new(&self.path, capacity, self.used, self.free))
+    }
+}
+
+fn validate_usage(
+    name: &str,
+    capacity: u64,
+    used: Option<u64>,
+    free: Option<u64>,
+) -> WorkerResult<()> {
+    if used.is_some_and(|used| used > capacity) || free.is_some_and(|free| free > capacity) {
+        return Err(WorkerError::disk_output_error(&format!(
+            "usage of {} exceeds its capacity",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(output: &str) -> String {
+        format!("{}", parse(output).unwrap_err())
+    }
+
+    #[test]
+    fn parse_lines_format() {
+        let disk = parse("1000\n6000\n4000\n5000\n").unwrap();
+
+        assert_eq!(disk.offset(), 1000);
+        assert_eq!(disk.capacity(), 6000);
+        assert_eq!(disk.soft_threshold(), 4000);
+        assert_eq!(disk.hard_threshold(), 5000);
+        assert_eq!(disk.used(), None);
+        assert!(disk.mounts().is_empty());
+    }
+
+    #[test]
+    fn parse_key_value_format() {
+        let disk = parse(
+            "# comment\noffset=1000\ncapacity=6000\nsoft_threshold=4000\nhard_threshold=5000\nfree=2500",
+        )
+        .unwrap();
+
+        assert_eq!(disk.capacity(), 6000);
+        assert_eq!(disk.used(), Some(3500));
+    }
+
+    #[test]
+    fn parse_key_value_mounts() {
+        let disk = parse(
+            "offset=0\nsoft_threshold=4000\nhard_threshold=5000\n\
+             mount./var/lib/pg.9.capacity=4000\nmount./var/lib/pg.9.used=1000\n\
+             mount./data.capacity=2000\nmount./data.free=500",
+        )
+        .unwrap();
+
+        assert_eq!(disk.capacity(), 6000);
+        assert_eq!(disk.used(), Some(2500));
+        assert_eq!(disk.mounts().len(), 2);
+        assert_eq!(disk.mounts()[0].path(), "/var/lib/pg.9");
+        assert_eq!(disk.mounts()[1].used(), Some(1500));
+    }
+
+    #[test]
+    fn parse_json_format() {
+        let disk = parse(
+            r#"{"offset": 1000, "soft_threshold": 4000, "hard_threshold": 5000, "used": 3000,
+                "mounts": [{"path": "/data", "capacity": 6000}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(disk.capacity(), 6000);
+        assert_eq!(disk.used(), Some(3000));
+        assert_eq!(disk.mounts()[0].used(), None);
+    }
+
+    #[test]
+    fn parse_uses_offset_when_mount_usage_is_incomplete() {
+        let disk = parse(
+            "offset=0\nsoft_threshold=1\nhard_threshold=2\n\
+             mount./a.capacity=10\nmount./a.used=5\nmount./b.capacity=10",
+        )
+        .unwrap();
+
+        assert_eq!(disk.used(), None);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_output() {
+        assert_eq!(
+            error("1000\n6000\n4000"),
+            "invalid disk command output - expected four lines with offset, capacity, soft and hard thresholds"
+        );
+        assert_eq!(
+            error("offset=1\noffset=2"),
+            "invalid disk command output - duplicate key `offset`"
+        );
+        assert_eq!(
+            error("offset=1\nsize=2"),
+            "invalid disk command output - unknown key `size`"
+        );
+        assert_eq!(
+            error("offset=many"),
+            "invalid disk command output - `many` is not a valid value of `offset`"
+        );
+        assert_eq!(
+            error("offset=1\ncapacity=10\nsoft_threshold=5\nhard_threshold=8\nused=11"),
+            "invalid disk command output - usage of disk exceeds its capacity"
+        );
+        assert_eq!(
+            error("offset=1\nsoft_threshold=5\nhard_threshold=8"),
+            "invalid disk command output - `capacity` is missing"
+        );
+        assert!(error(r#"{"offset": 1, "extra": 2}"#).contains("unknown field `extra`"));
+    }
+}
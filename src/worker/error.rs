@@ -1,4 +1,7 @@
+use crate::history::HistoryError;
+use crate::postgres::DatabaseError;
 use crate::state::StateError;
+use crate::state::Timeout;
 use std::error::Error;
 use std::fmt::Display;
 use std::fmt::Formatter;
@@ -8,9 +11,16 @@ use std::io::Error as IoError;
 pub type WorkerResult<T> = Result<T, WorkerError>;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum WorkerError {
     StateError { message: String },
     IoError { message: String },
+    HistoryError { message: String },
+    DatabaseError { message: String },
+    ConnectTimeout { message: String },
+    StatementTimeout { message: String },
+    DiskCommandTimeout { command: String, timeout: u64 },
+    DiskOutputError { message: String },
 }
 
 impl WorkerError {
@@ -27,6 +37,47 @@ impl WorkerError {
             message: format!("{}", error),
         }
     }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn history_error(error: HistoryError) -> WorkerError {
+        WorkerError::HistoryError {
+            message: format!("{}", error),
+        }
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn database_error(error: DatabaseError) -> WorkerError {
+        let message = format!("{}", error);
+
+        match error {
+            DatabaseError::ConnectTimeout { .. } => WorkerError::ConnectTimeout { message },
+            DatabaseError::StatementTimeout { .. } => WorkerError::StatementTimeout { message },
+            _ => WorkerError::DatabaseError { message },
+        }
+    }
+
+    pub fn disk_command_timeout(command: &str, timeout: u64) -> WorkerError {
+        WorkerError::DiskCommandTimeout {
+            command: command.into(),
+            timeout,
+        }
+    }
+
+    pub fn disk_output_error(message: &str) -> WorkerError {
+        WorkerError::DiskOutputError {
+            message: message.into(),
+        }
+    }
+
+    /// Returns kind of timeout if error is caused by timeout.
+    pub fn timeout(&self) -> Option<Timeout> {
+        match self {
+            WorkerError::ConnectTimeout { .. } => Some(Timeout::Connect),
+            WorkerError::StatementTimeout { .. } => Some(Timeout::Statement),
+            WorkerError::DiskCommandTimeout { .. } => Some(Timeout::DiskCommand),
+            _ => None,
+        }
+    }
 }
 
 impl Error for WorkerError {}
@@ -36,6 +87,18 @@ impl Display for WorkerError {
         match self {
             WorkerError::StateError { message } => write!(f, "{}", message),
             WorkerError::IoError { message } => write!(f, "{}", message),
+            WorkerError::HistoryError { message } => write!(f, "{}", message),
+            WorkerError::DatabaseError { message } => write!(f, "{}", message),
+            WorkerError::ConnectTimeout { message } => write!(f, "{}", message),
+            WorkerError::StatementTimeout { message } => write!(f, "{}", message),
+            WorkerError::DiskCommandTimeout { command, timeout } => write!(
+                f,
+                "disk command `{}` did not complete in {} seconds",
+                command, timeout
+            ),
+            WorkerError::DiskOutputError { message } => {
+                write!(f, "invalid disk command output - {}", message)
+            }
         }
     }
 }
@@ -1,179 +1,428 @@
+mod attribution;
+mod disk;
 mod error;
+mod retention;
 
 pub use self::error::WorkerError;
 pub use self::error::WorkerResult;
+pub use self::retention::plan as retention_plan;
+pub use self::retention::Candidate;
 
+use self::attribution::Attribution;
+use crate::audit::AuditRef;
+use crate::clock;
+use crate::config::BloatConfig;
 use crate::config::ConfigRef;
 use crate::config::DiskConfig;
+use crate::config::ServerConfig;
+use crate::config::SharedConfig;
+use crate::history::HistoryRef;
+use crate::history::Sample;
 use crate::postgres::PostgreSQL;
+use crate::postgres::PostgresRef;
+use crate::state::Database;
+use crate::state::DiskState;
 use crate::state::StateRef;
+use crate::state::WorkerStage;
+use std::collections::HashMap;
+use std::io::Error as IoError;
 use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::process::Child;
 use std::process::Command;
 use std::process::Stdio;
+use std::sync::mpsc;
 use std::thread;
 use std::thread::Builder;
 use std::time::Duration;
+use std::time::Instant;
+
+const COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 #[derive(Debug)]
 pub struct Worker {
-    config: ConfigRef,
+    config: SharedConfig,
     state: StateRef,
+    postgres: PostgresRef,
+    history: Option<HistoryRef>,
+    audit: Option<AuditRef>,
 }
 
 impl Worker {
-    pub fn new(config: ConfigRef, state: StateRef) -> Worker {
-        Worker { config, state }
+    pub fn new(
+        config: SharedConfig,
+        state: StateRef,
+        postgres: PostgresRef,
+        history: Option<HistoryRef>,
+        audit: Option<AuditRef>,
+    ) -> Worker {
+        Worker {
+            config,
+            state,
+            postgres,
+            history,
+            audit,
+        }
     }
 
     fn start(self) {
-        let interval = self.config.update_interval();
-
-        if let DiskConfig::Fixed {
-            offset,
-            capacity,
-            soft_threshold,
-            hard_threshold,
-        } = self.config.server().disk()
-        {
-            if let Err(err) =
-                self.state
-                    .set_disk_state(*offset, *capacity, *soft_threshold, *hard_threshold)
-            {
-                warn!("Update state error: {}", err);
+        if let Some(activity) = self.config.get().activity() {
+            if let Err(err) = self.state.load_activity(activity.path()) {
+                warn!("Failed to load database activity: {}", err);
             }
         }
 
         loop {
-            info!("Start query databases");
+            // Configuration may be reloaded between cycles, every cycle uses the same snapshot.
+            let config = self.config.get();
 
-            if let Err(err) = update_disk(&self.config, &self.state) {
-                warn!("Update disk error: {}", err);
+            for server_config in config.servers() {
+                self.update_server(&config, server_config);
             }
 
-            if let Err(err) = update_databases(&self.config, &self.state) {
-                warn!("Update database error: {}", err);
-            }
+            thread::sleep(Duration::from_secs(config.update_interval()));
+        }
+    }
+
+    fn update_server(&self, config: &ConfigRef, server_config: &ServerConfig) {
+        let server_name = server_config.name();
+        let postgres = self.postgres.server(server_config);
+        let started = Instant::now();
+        let mut success = true;
+
+        info!("Start query databases on {}", server_name);
+
+        let result = update_disk(server_config, &self.state);
+        success &= record_stage(&self.state, server_name, WorkerStage::Disk, &result);
+
+        let result = update_databases(config, server_config, &postgres, &self.state)
+            .and_then(|_| save_activity(config, &self.state));
+        let databases_updated =
+            record_stage(&self.state, server_name, WorkerStage::Databases, &result);
+        success &= databases_updated;
+
+        if let Some(bloat) = config.bloat() {
+            let result = update_bloat(bloat, server_config, &postgres, &self.state);
+            success &= record_stage(&self.state, server_name, WorkerStage::Bloat, &result);
+        }
+
+        // Retention rules must not be evaluated against stale databases.
+        if let Some(retention) = config.retention().filter(|_| databases_updated) {
+            let now = clock::unix_timestamp();
+            let result = retention::apply(
+                retention,
+                server_config,
+                &postgres,
+                &self.state,
+                self.audit.as_ref(),
+                now,
+            );
+            success &= record_stage(&self.state, server_name, WorkerStage::Retention, &result);
+        }
+
+        // Sizes of failed update are stale or cleared and must not be recorded as samples.
+        if let Some(history) = self.history.as_ref().filter(|_| databases_updated) {
+            let result = update_history(server_config, &self.state, history);
+            success &= record_stage(&self.state, server_name, WorkerStage::History, &result);
+        }
+
+        let duration = started.elapsed().as_secs_f64();
 
-            info!("Query complete");
+        if let Err(err) =
+            self.state
+                .complete_cycle(server_name, clock::unix_timestamp(), duration, success)
+        {
+            warn!("Update state error: {}", err);
+        }
+
+        info!("Query complete on {}", server_name);
+    }
+}
+
+/// Record result of the update stage in worker status. Returns `true` if stage completed successfully.
+pub fn record_stage(
+    state: &StateRef,
+    server_name: &str,
+    stage: WorkerStage,
+    result: &WorkerResult<()>,
+) -> bool {
+    let now = clock::unix_timestamp();
+    let recorded = match result {
+        Ok(()) => state.add_success(server_name, stage, now),
+        Err(err) => {
+            warn!("Update {} error on {}: {}", stage.name(), server_name, err);
 
-            thread::sleep(Duration::from_secs(interval));
+            state.add_error(server_name, stage, now, &format!("{}", err), err.timeout())
         }
+    };
+
+    if let Err(err) = recorded {
+        warn!("Update state error: {}", err);
     }
+
+    result.is_ok()
 }
 
 /// Update disk usage info using command defined in the configuration. This function will block callee.
 ///
 /// If some error occurred content of state is not defined.
-pub fn update_disk(config: &ConfigRef, state: &StateRef) -> WorkerResult<()> {
-    let mut offset = 0;
-    let mut capacity = 0;
-    let mut soft_threshold = 0;
-    let mut hard_threshold = 0;
-
-    if let DiskConfig::Command { command } = config.server().disk() {
-        let mut child = Command::new(command)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .env_clear()
-            .spawn()
-            .map_err(WorkerError::io_error)?;
-
-        if let Some(ref mut stdout) = child.stdout {
-            let mut buffer = String::default();
-
-            stdout
-                .read_to_string(&mut buffer)
-                .map_err(WorkerError::io_error)?;
-
-            let mut lines = buffer.lines().map(|line| line.trim());
-
-            offset = lines
-                .next()
-                .map(|value| value.parse().unwrap_or(0))
-                .unwrap_or(0);
-            capacity = lines
-                .next()
-                .map(|value| value.parse().unwrap_or(0))
-                .unwrap_or(0);
-            soft_threshold = lines
-                .next()
-                .map(|value| value.parse().unwrap_or(0))
-                .unwrap_or(0);
-            hard_threshold = lines
-                .next()
-                .map(|value| value.parse().unwrap_or(0))
-                .unwrap_or(0);
+pub fn update_disk(server_config: &ServerConfig, state: &StateRef) -> WorkerResult<()> {
+    let disk_state = match server_config.disk() {
+        DiskConfig::Fixed {
+            offset,
+            capacity,
+            soft_threshold,
+            hard_threshold,
+        } => DiskState::new(*offset, *capacity, *soft_threshold, *hard_threshold),
+        DiskConfig::Command {
+            command,
+            args,
+            env,
+            timeout,
+        } => disk::parse(&run_command(command, args, env, *timeout)?)?,
+    };
+
+    state
+        .set_disk_state(server_config.name(), disk_state)
+        .map_err(WorkerError::state_error)
+}
+
+/// Run command with given arguments and environment and return its output. Environment of the application is not
+/// passed to the command. If command does not exit in `timeout` seconds, it will be killed.
+fn run_command(
+    command: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+    timeout: u64,
+) -> WorkerResult<String> {
+    let deadline = Instant::now() + Duration::from_secs(timeout);
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .env_clear()
+        .envs(env)
+        .process_group(0)
+        .spawn()
+        .map_err(WorkerError::io_error)?;
+    let stdout = child.stdout.take();
+    let (sender, receiver) = mpsc::channel();
+
+    // Output is read in separate thread, so hung command will not block reading.
+    thread::spawn(move || {
+        let mut buffer = String::default();
+        let result = match stdout {
+            Some(mut stdout) => stdout.read_to_string(&mut buffer).map(|_| buffer),
+            None => Ok(buffer),
+        };
+
+        sender.send(result).ok();
+    });
+
+    let output = match receiver.recv_timeout(Duration::from_secs(timeout)) {
+        Ok(output) => output.map_err(WorkerError::io_error)?,
+        Err(_) => return Err(kill_command(&mut child, command, timeout)),
+    };
+
+    loop {
+        if child.try_wait().map_err(WorkerError::io_error)?.is_some() {
+            return Ok(output);
         }
 
-        child.wait().map_err(WorkerError::io_error)?;
+        if Instant::now() >= deadline {
+            return Err(kill_command(&mut child, command, timeout));
+        }
+
+        thread::sleep(COMMAND_POLL_INTERVAL);
     }
+}
 
-    state
-        .set_disk_state(offset, capacity, soft_threshold, hard_threshold)
-        .map_err(WorkerError::state_error)
+/// Kill command with all processes started by it. Command is started in its own process group, so the whole group is
+/// killed.
+fn kill_command(child: &mut Child, command: &str, timeout: u64) -> WorkerError {
+    // SAFETY: `kill` has no memory safety requirements, negative PID selects process group of the child.
+    let result = if unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) } == 0 {
+        child.wait().map(|_| ())
+    } else {
+        Err(IoError::last_os_error())
+    };
+
+    if let Err(err) = result {
+        warn!("Failed to kill disk command {} - {}", command, err);
+    }
+
+    WorkerError::disk_command_timeout(command, timeout)
 }
 
-/// Update state to match all databases in query. This function will block callee until all databases updated.
+/// Save activity of databases if it is configured. Activity is saved only by the running server, so commands do not
+/// overwrite its file.
+pub fn save_activity(config: &ConfigRef, state: &StateRef) -> WorkerResult<()> {
+    match config.activity() {
+        Some(activity) => state
+            .save_activity(activity.path())
+            .map_err(WorkerError::state_error),
+        None => Ok(()),
+    }
+}
+
+/// Update state to match all databases in query. This function will block callee until all databases updated. Activity
+/// is updated only in memory, it is saved by `save_activity`.
 ///
 /// If some error occurred content of state is not defined.
-pub fn update_databases(config: &ConfigRef, state: &StateRef) -> WorkerResult<()> {
-    let server_config = config.server();
-    let all_users: Vec<_> = config
-        .users()
-        .iter()
-        .map(|user| user.login().to_lowercase())
-        .collect();
-    let postgres = PostgreSQL::new(
-        server_config.host(),
-        server_config.port(),
-        server_config.role(),
-        server_config.password(),
-    );
-
-    match postgres.database_list(|name, modified, size| DatabaseInfo::new(name, modified, size)) {
+pub fn update_databases(
+    config: &ConfigRef,
+    server_config: &ServerConfig,
+    postgres: &PostgreSQL,
+    state: &StateRef,
+) -> WorkerResult<()> {
+    let server_name = server_config.name();
+
+    match postgres.database_list() {
         Ok(infos) => {
-            state.clear().map_err(WorkerError::state_error)?;
+            let mut session_stats = match postgres.session_stats() {
+                Ok(session_stats) => session_stats,
+                Err(err) => {
+                    warn!("Failed to query session statistics - {}", err);
+
+                    HashMap::new()
+                }
+            };
+
+            match postgres.database_stats() {
+                Ok(stats) => {
+                    let queried = postgres.queried_databases();
+
+                    state
+                        .update_activity(server_name, stats, &queried, clock::unix_timestamp())
+                        .map_err(WorkerError::state_error)?;
+                }
+                Err(err) => warn!("Failed to query database statistics - {}", err),
+            }
+
+            state.clear(server_name).map_err(WorkerError::state_error)?;
 
             for info in infos {
-                let database_name = info.name.to_lowercase();
-                let user = all_users
-                    .iter()
-                    .find(|&login| database_name.contains(login));
+                let attribution = attribution::attribute(config, &info);
+                let sessions = session_stats.remove(info.name()).unwrap_or_default();
+                let database = Database::new(
+                    info,
+                    attribution.as_ref().map(Attribution::user),
+                    attribution.as_ref().map(Attribution::rule),
+                    sessions,
+                );
 
                 state
-                    .put(&info.name, user, info.modified, info.size)
+                    .put(server_name, database)
                     .map_err(WorkerError::state_error)?;
             }
         }
-        Err(err) => warn!("Failed to retain old paths - {}", err),
+        Err(err) => return Err(WorkerError::database_error(err)),
     }
 
     Ok(())
 }
 
-pub fn start(config: ConfigRef, state: StateRef) {
-    if let Err(err) = Builder::new()
-        .name("state worker".to_string())
-        .spawn(move || Worker::new(config, state).start())
-    {
-        warn!("Failed to start state worker - {}", err);
+/// Estimate bloat of every database which allows connections and has no bloat estimation newer than configured
+/// interval. Estimation of all databases is attempted even if some of them failed, the last error is returned.
+pub fn update_bloat(
+    bloat_config: &BloatConfig,
+    server_config: &ServerConfig,
+    postgres: &PostgreSQL,
+    state: &StateRef,
+) -> WorkerResult<()> {
+    let server_name = server_config.name();
+    let mut names = Vec::new();
+    let mut result = Ok(());
+
+    state
+        .for_each(server_name, |database| {
+            // Connections to templates prevent creation of databases from them.
+            if database.info().allow_connections() && !database.info().is_template() {
+                names.push(database.name().to_string());
+            }
+        })
+        .map_err(WorkerError::state_error)?;
+
+    for name in names {
+        let now = clock::unix_timestamp();
+        let cached = state
+            .bloat(server_name, &name, now, bloat_config.interval())
+            .map_err(WorkerError::state_error)?;
+
+        if cached.is_some() {
+            continue;
+        }
+
+        match postgres.bloat(&name, bloat_config.pgstattuple()) {
+            Ok(bloat) => state
+                .set_bloat(server_name, &name, bloat, now)
+                .map_err(WorkerError::state_error)?,
+            Err(err) => {
+                warn!("Failed to estimate bloat of database {} - {}", name, err);
+
+                result = Err(WorkerError::database_error(err));
+            }
+        }
     }
+
+    result
 }
 
-#[derive(Debug)]
-struct DatabaseInfo {
-    name: String,
-    modified: i64,
-    size: u64,
+/// Append current state of the server databases and disk to the history.
+pub fn update_history(
+    server_config: &ServerConfig,
+    state: &StateRef,
+    history: &HistoryRef,
+) -> WorkerResult<()> {
+    let server_name = server_config.name();
+    let now = clock::unix_timestamp();
+    let mut samples = Vec::new();
+
+    state
+        .for_each(server_name, |database| {
+            samples.push(Sample::Database {
+                server: server_name.into(),
+                time: now,
+                name: database.name().into(),
+                size: database.size(),
+            });
+        })
+        .map_err(WorkerError::state_error)?;
+
+    let disk = state
+        .disk_state(server_name)
+        .map_err(WorkerError::state_error)?;
+    let used = state
+        .disk_used(server_config)
+        .map_err(WorkerError::state_error)?;
+
+    samples.push(Sample::Disk {
+        server: server_name.into(),
+        time: now,
+        used,
+        capacity: disk.capacity(),
+        soft_threshold: disk.soft_threshold(),
+        hard_threshold: disk.hard_threshold(),
+        databases: samples.len(),
+    });
+
+    history
+        .append(samples, now)
+        .map_err(WorkerError::history_error)
 }
 
-impl DatabaseInfo {
-    fn new(name: &str, modified: i64, size: u64) -> DatabaseInfo {
-        DatabaseInfo {
-            name: name.into(),
-            modified,
-            size,
-        }
+pub fn start(
+    config: SharedConfig,
+    state: StateRef,
+    postgres: PostgresRef,
+    history: Option<HistoryRef>,
+    audit: Option<AuditRef>,
+) {
+    if let Err(err) = Builder::new()
+        .name("state worker".to_string())
+        .spawn(move || Worker::new(config, state, postgres, history, audit).start())
+    {
+        warn!("Failed to start state worker - {}", err);
     }
 }
@@ -0,0 +1,453 @@
+use super::WorkerError;
+use super::WorkerResult;
+use crate::audit::AuditRef;
+use crate::audit::Record;
+use crate::clock;
+use crate::config::RetentionConfig;
+use crate::config::RetentionRule;
+use crate::config::ServerConfig;
+use crate::postgres::PostgreSQL;
+use crate::state::Database;
+use crate::state::StateRef;
+use std::collections::HashSet;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+const AUDIT_REMOTE_ADDRESS: &str = "retention";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Candidate {
+    name: String,
+    user: Option<String>,
+    size: u64,
+    modified: i64,
+    last_active: Option<i64>,
+    reason: String,
+}
+
+impl Candidate {
+    fn new(database: &Database, reason: String) -> Candidate {
+        Candidate {
+            name: database.name().into(),
+            user: database.user().cloned(),
+            size: database.size(),
+            modified: database.modified(),
+            last_active: database.last_active(),
+            reason,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+/// Evaluate retention rules for all databases of the server. Returns databases which should be dropped with the
+/// reason of the first matched rule. Service and template databases, databases which do not allow connections and
+/// databases with sessions are never included.
+pub fn plan(
+    retention: &RetentionConfig,
+    server_config: &ServerConfig,
+    state: &StateRef,
+    now: i64,
+) -> WorkerResult<Vec<Candidate>> {
+    let server_name = server_config.name();
+    let mut databases = Vec::new();
+
+    state
+        .for_each(server_name, |database| databases.push(database.clone()))
+        .map_err(WorkerError::state_error)?;
+
+    let disk = if retention
+        .rules()
+        .iter()
+        .any(|rule| matches!(rule, RetentionRule::DiskHardThreshold))
+    {
+        Disk {
+            used: state
+                .disk_used(server_config)
+                .map_err(WorkerError::state_error)?,
+            hard_threshold: state
+                .disk_state(server_name)
+                .map_err(WorkerError::state_error)?
+                .hard_threshold(),
+        }
+    } else {
+        Disk::default()
+    };
+
+    Ok(select(
+        retention.rules(),
+        server_config.service_databases(),
+        databases,
+        disk,
+        now,
+    ))
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Disk {
+    used: u64,
+    hard_threshold: u64,
+}
+
+fn select(
+    rules: &[RetentionRule],
+    service_databases: &HashSet<String>,
+    mut databases: Vec<Database>,
+    disk: Disk,
+    now: i64,
+) -> Vec<Candidate> {
+    databases.retain(|database| {
+        let info = database.info();
+
+        // Databases with sessions are refused by drop, so they are not planned until sessions are closed.
+        !service_databases.contains(database.name())
+            && !info.is_template()
+            && info.allow_connections()
+            && !database.sessions().has_sessions()
+    });
+
+    // Oldest databases go first.
+    databases.sort_by_key(|database| database.modified());
+
+    let mut selected = HashSet::new();
+    let mut candidates = Vec::new();
+
+    for rule in rules {
+        match rule {
+            RetentionRule::UserDatabaseAge { days } => {
+                let border = now - *days as i64 * SECONDS_PER_DAY;
+
+                for database in &databases {
+                    if let Some(user) = database.user() {
+                        if database.modified() < border && selected.insert(database.name()) {
+                            let reason =
+                                format!("database of user {} is older than {} days", user, days);
+
+                            candidates.push(Candidate::new(database, reason));
+                        }
+                    }
+                }
+            }
+            RetentionRule::PatternAge { pattern, days } => {
+                let border = now - *days as i64 * SECONDS_PER_DAY;
+
+                // Databases without known activity are skipped, creation time says nothing about their use.
+                for database in &databases {
+                    if pattern.is_match(database.name())
+                        && database
+                            .last_active()
+                            .is_some_and(|last_active| last_active < border)
+                        && selected.insert(database.name())
+                    {
+                        let reason = format!(
+                            "database matches pattern `{}` and was not active for {} days",
+                            pattern.as_str(),
+                            days
+                        );
+
+                        candidates.push(Candidate::new(database, reason));
+                    }
+                }
+            }
+            RetentionRule::DiskHardThreshold => {
+                let freed: u64 = candidates.iter().map(|candidate| candidate.size).sum();
+                let mut remaining = disk.used.saturating_sub(freed);
+
+                // Zero threshold means disk state is unknown, nothing can be dropped in this case.
+                if disk.hard_threshold == 0 {
+                    continue;
+                }
+
+                for database in &databases {
+                    if remaining <= disk.hard_threshold {
+                        break;
+                    }
+
+                    if selected.insert(database.name()) {
+                        remaining = remaining.saturating_sub(database.size());
+
+                        candidates.push(Candidate::new(
+                            database,
+                            "disk usage exceeds hard threshold".into(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Drop all databases selected by retention rules. In dry run mode candidates will be only logged. Databases which have
+/// sessions are never dropped, every drop attempt is written to audit log.
+///
+/// If some databases failed to drop, other databases will be dropped anyway and the first error will be returned.
+pub fn apply(
+    retention: &RetentionConfig,
+    server_config: &ServerConfig,
+    postgres: &PostgreSQL,
+    state: &StateRef,
+    audit: Option<&AuditRef>,
+    now: i64,
+) -> WorkerResult<()> {
+    let server_name = server_config.name();
+    let candidates = plan(retention, server_config, state, now)?;
+
+    if retention.dry_run() {
+        for candidate in &candidates {
+            info!(
+                "Retention candidate {} on {} - {}",
+                candidate.name(),
+                server_name,
+                candidate.reason()
+            );
+        }
+
+        return Ok(());
+    }
+
+    let mut result = Ok(());
+
+    for candidate in &candidates {
+        let database = state
+            .database(server_name, candidate.name())
+            .map_err(WorkerError::state_error)?;
+        // Sessions are checked regardless of server settings, so sessions are never terminated automatically.
+        let drop_result = postgres.drop_database(
+            candidate.name(),
+            server_config.service_databases(),
+            true,
+            false,
+        );
+
+        if let Some(audit) = audit {
+            let record = Record::new(
+                clock::unix_timestamp(),
+                AUDIT_REMOTE_ADDRESS,
+                None,
+                server_name,
+                candidate.name(),
+            )
+            .with_database(database.as_ref())
+            .with_outcome(drop_result.as_ref().err().map(|err| format!("{}", err)));
+
+            if let Err(err) = audit.append(&record) {
+                error!("Failed to write audit record - {}", err);
+            }
+        }
+
+        match drop_result {
+            Ok(()) => {
+                info!(
+                    "Retention dropped {} on {} - {}",
+                    candidate.name(),
+                    server_name,
+                    candidate.reason()
+                );
+
+                state
+                    .remove(server_name, candidate.name())
+                    .map_err(WorkerError::state_error)?;
+            }
+            Err(err) => {
+                warn!(
+                    "Retention failed to drop {} on {} - {}",
+                    candidate.name(),
+                    server_name,
+                    err
+                );
+
+                if result.is_ok() {
+                    result = Err(WorkerError::database_error(err));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::DatabaseInfo;
+    use crate::postgres::SessionStats;
+
+    const NOW: i64 = 100 * SECONDS_PER_DAY;
+
+    fn database(name: &str, age_days: i64, size: u64, user: Option<&str>) -> Database {
+        let info = DatabaseInfo::new(name, NOW - age_days * SECONDS_PER_DAY, size);
+
+        Database::new(info, user, None, SessionStats::default())
+    }
+
+    fn rules(yaml: &str) -> Vec<RetentionRule> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn names(candidates: &[Candidate]) -> Vec<&str> {
+        candidates.iter().map(Candidate::name).collect()
+    }
+
+    #[test]
+    fn plan_selects_old_user_databases() {
+        let databases = vec![
+            database("anaconda_new", 1, 10, Some("anaconda")),
+            database("anaconda_old", 40, 10, Some("anaconda")),
+            database("orphan_old", 40, 10, None),
+        ];
+        let candidates = select(
+            &rules("- type: UserDatabaseAge\n  days: 30"),
+            &HashSet::new(),
+            databases,
+            Disk::default(),
+            NOW,
+        );
+
+        assert_eq!(names(&candidates), vec!["anaconda_old"]);
+    }
+
+    #[test]
+    fn plan_excludes_service_template_and_not_connectable_databases() {
+        let template = database("template_old", 40, 10, Some("anaconda"));
+        let template = Database::new(
+            template.info().clone().with_flags(true, true),
+            Some("anaconda"),
+            None,
+            SessionStats::default(),
+        );
+        let locked = database("locked_old", 40, 10, Some("anaconda"));
+        let locked = Database::new(
+            locked.info().clone().with_flags(false, false),
+            Some("anaconda"),
+            None,
+            SessionStats::default(),
+        );
+        let databases = vec![
+            database("service_old", 40, 10, Some("anaconda")),
+            template,
+            locked,
+        ];
+        let service_databases = vec!["service_old".to_string()].into_iter().collect();
+        let candidates = select(
+            &rules("- type: UserDatabaseAge\n  days: 30"),
+            &service_databases,
+            databases,
+            Disk::default(),
+            NOW,
+        );
+
+        assert!(candidates.is_empty());
+    }
+
+    #[test]
+    fn plan_excludes_databases_with_sessions() {
+        let connected = database("connected_old", 40, 10, Some("anaconda"));
+        let connected = Database::new(
+            connected.info().clone(),
+            Some("anaconda"),
+            None,
+            SessionStats::idle(1),
+        );
+        let candidates = select(
+            &rules("- type: UserDatabaseAge\n  days: 30\n- type: DiskHardThreshold"),
+            &HashSet::new(),
+            vec![connected, database("free_old", 40, 10, Some("anaconda"))],
+            Disk {
+                used: 100,
+                hard_threshold: 50,
+            },
+            NOW,
+        );
+
+        assert_eq!(names(&candidates), vec!["free_old"]);
+    }
+
+    #[test]
+    fn plan_matches_pattern_by_last_activity() {
+        let databases = vec![
+            database("test_active", 40, 10, None).with_last_active(Some(NOW - SECONDS_PER_DAY)),
+            database("test_idle", 1, 10, None).with_last_active(Some(NOW - 10 * SECONDS_PER_DAY)),
+            database("test_unknown", 40, 10, None),
+            database("prod_idle", 40, 10, None).with_last_active(Some(NOW - 10 * SECONDS_PER_DAY)),
+        ];
+        let candidates = select(
+            &rules("- type: PatternAge\n  pattern: \"^test_\"\n  days: 7"),
+            &HashSet::new(),
+            databases,
+            Disk::default(),
+            NOW,
+        );
+
+        assert_eq!(names(&candidates), vec!["test_idle"]);
+    }
+
+    #[test]
+    fn plan_drops_oldest_databases_above_hard_threshold() {
+        let databases = vec![
+            database("newest", 1, 30, None),
+            database("oldest", 3, 30, None),
+            database("middle", 2, 30, None),
+        ];
+        let disk = Disk {
+            used: 100,
+            hard_threshold: 50,
+        };
+        let candidates = select(
+            &rules("- type: DiskHardThreshold"),
+            &HashSet::new(),
+            databases,
+            disk,
+            NOW,
+        );
+
+        assert_eq!(names(&candidates), vec!["oldest", "middle"]);
+    }
+
+    #[test]
+    fn plan_counts_earlier_candidates_against_hard_threshold() {
+        let databases = vec![
+            database("anaconda_old", 40, 60, Some("anaconda")),
+            database("orphan", 2, 30, None),
+        ];
+        let disk = Disk {
+            used: 100,
+            hard_threshold: 50,
+        };
+        let candidates = select(
+            &rules("- type: UserDatabaseAge\n  days: 30\n- type: DiskHardThreshold"),
+            &HashSet::new(),
+            databases,
+            disk,
+            NOW,
+        );
+
+        assert_eq!(names(&candidates), vec!["anaconda_old"]);
+        assert_eq!(
+            candidates[0].reason(),
+            "database of user anaconda is older than 30 days"
+        );
+    }
+
+    #[test]
+    fn plan_ignores_hard_threshold_of_unknown_disk() {
+        let candidates = select(
+            &rules("- type: DiskHardThreshold"),
+            &HashSet::new(),
+            vec![database("oldest", 3, 30, None)],
+            Disk {
+                used: 100,
+                hard_threshold: 0,
+            },
+            NOW,
+        );
+
+        assert!(candidates.is_empty());
+    }
+}